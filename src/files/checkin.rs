@@ -1,97 +1,178 @@
-use std::collections::HashMap;
+use quick_xml::escape::unescape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
 use reqwest::Client;
-use xml::builder::*;
+use url::Url;
+
+use super::createFile::SharePointClient;
+use crate::utils::buildBodyForSOAP::build_body_for_soap;
+use crate::utils::escapeXML::escape_xml;
+use crate::utils::getURL::{discover_url, UrlNotFound};
+use crate::utils::resolveURL::resolve_url;
 
 #[derive(Debug)]
 pub struct Error(String);
 
-pub async fn checkin(setup: Option<HashMap<String, String>>) -> Result<(), Error> {
-    let mut setup = setup.unwrap_or_default();
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error(format!("[SharepointSharp 'checkin'] {}", e))
+    }
+}
+
+impl From<UrlNotFound> for Error {
+    fn from(e: UrlNotFound) -> Self {
+        Error(e.to_string())
+    }
+}
 
-    // Validate destination
-    if !setup.contains_key("destination") {
-        return Err(Error("[SharepointSharp 'checkin'] the file destination path is required.".to_string()));
+impl From<url::ParseError> for Error {
+    fn from(e: url::ParseError) -> Self {
+        Error(format!("[SharepointSharp 'checkin'] invalid URL: {}", e))
     }
+}
 
-    // Set URL if not provided
-    if !setup.contains_key("url") {
-        // Implement your logic to get the URL (replace with your actual implementation)
-        let url = get_url().await?; // Replace with your URL retrieval function
-        setup.insert("url".to_string(), url);
+impl From<quick_xml::Error> for Error {
+    fn from(e: quick_xml::Error) -> Self {
+        Error(format!("[SharepointSharp 'checkin'] invalid response: {}", e))
     }
+}
+
+/// `CheckinType` of `CheckInFile`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CheckinType {
+    Minor = 0,
+    #[default]
+    Major = 1,
+    Overwrite = 2,
+}
+
+#[derive(Default)]
+pub struct CheckinOptions {
+    /// Full URL of the file to check in
+    pub destination: String,
+    pub comments: String,
+    pub checkin_type: CheckinType,
+}
+
+impl SharePointClient {
+    pub async fn checkin(&self, options: CheckinOptions) -> Result<(), Error> {
+        if options.destination.is_empty() {
+            return Err(Error("[SharepointSharp 'checkin'] the file destination path is required.".to_string()));
+        }
+        let site = Url::parse(&discover_url(Some(&self.url))?)?;
+        let url = resolve_url(&site, "_vti_bin/Lists.asmx")?;
+        let soap_body = build_body_for_soap(
+            "CheckInFile",
+            &format!(
+                "<pageUrl>{}</pageUrl><comment>{}</comment><CheckinType>{}</CheckinType>",
+                escape_xml(&options.destination),
+                escape_xml(&options.comments),
+                options.checkin_type as i32
+            ),
+            None,
+        );
 
-    // Escape comments for XML
-    setup.insert("comments".to_string(), escape_xml(&setup["comments"].to_owned())?);
-
-    let checkin_type = match setup.get("type") {
-        Some("MinorCheckIn") => 0,
-        Some("OverwriteCheckIn") => 2,
-        _ => 1, // Default to MajorCheckIn
-    };
-
-    let url = format!("{}/_vti_bin/Lists.asmx", setup["url"].to_owned());
-    let soap_body = build_soap_body(
-        "CheckInFile",
-        vec![
-            ("pageUrl", &setup["destination"]),
-            ("comment", &setup["comments"]),
-            ("CheckinType", &checkin_type.to_string()),
-        ],
-    );
-
-    let client = Client::new();
-    let response = client
-        .post(&url)
-        .header("SOAPAction", "http://schemas.microsoft.com/sharepoint/soap/CheckInFile")
-        .body(soap_body.as_bytes())
-        .send()
-        .await?;
-
-    if response.status().is_success() {
+        let client = Client::new();
+        let response = client
+            .post(url)
+            .header("Content-Type", "text/xml; charset=utf-8")
+            .header("SOAPAction", "http://schemas.microsoft.com/sharepoint/soap/CheckInFile")
+            .body(soap_body)
+            .send()
+            .await?;
+
+        // a SOAP fault comes with a 500, its message is more useful than the status
+        let status = response.status();
         let response_xml = response.text().await?;
-        let doc = Document::parse_str(&response_xml)?;
-        let result_node = doc.find("//CheckInFileResult").unwrap();
-
-        if result_node.text() != "true" {
-            return Err(Error(format!(
-                "Check-in failed: {}",
-                result_node.text().unwrap_or_default()
-            )));
+        match parse_checkin_result(&response_xml)? {
+            CheckinResult::Done(true) => Ok(()),
+            CheckinResult::Done(false) => Err(Error("[SharepointSharp 'checkin'] Check-in failed.".to_string())),
+            CheckinResult::Fault(message) => Err(Error(format!("[SharepointSharp 'checkin'] Check-in failed: {}", message))),
+            CheckinResult::Missing => Err(Error(format!("[SharepointSharp 'checkin'] Failed to check in: {}", status))),
         }
-    } else {
-        return Err(Error(format!("Failed to check in: {}", response.status())));
     }
-
-    Ok(())
 }
 
-// Implement these functions as needed for your specific environment:
-async fn get_url() -> Result<String, Error> {
-    // Your logic to retrieve the URL
-    Err(Error("Not implemented".to_string()))
+#[derive(Debug, PartialEq, Eq)]
+enum CheckinResult {
+    Done(bool),
+    Fault(String),
+    Missing,
 }
 
-fn escape_xml(text: &str) -> Result<String, Error> {
-    // Implement XML escaping logic
-    Err(Error("Not implemented".to_string()))
+// Read <CheckInFileResult>, or the message of a SOAP fault (the <errorstring>
+// detail comes after <faultstring> and is the more precise one)
+fn parse_checkin_result(xml: &str) -> Result<CheckinResult, Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut fault = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"CheckInFileResult" => {
+                    let text = reader.read_text(e.name())?;
+                    return Ok(CheckinResult::Done(text.trim() == "true"));
+                }
+                b"faultstring" | b"errorstring" => {
+                    let text = reader.read_text(e.name())?;
+                    let text = unescape(&text).map_err(quick_xml::Error::from)?;
+                    fault = Some(text.trim().to_string());
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(fault.map_or(CheckinResult::Missing, CheckinResult::Fault))
 }
 
-fn build_soap_body(method: &str, elements: Vec<(&str, &str)>) -> String {
-    let mut builder = Builder::new();
-    builder = builder.element("soapenv:Envelope", {
-        attr("xmlns:soapenv", "http://schemas.xmlsoap.org/soap/envelope/");
-        attr("xmlns:ns0", "http://schemas.microsoft.com/sharepoint/soap/");
-    });
-    builder = builder.push(builder::element("soapenv:Header", Vec::new()));
-    builder = builder.push(builder::element(
-        "soapenv:Body",
-        vec![builder::element(
-            "ns0:".to_owned() + method,
-            elements.into_iter().map(|&(name, value)| {
-                builder::element(name, value)
-            }).collect(),
-        )],
-    ));
-
-    builder.as_str().to_string()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(body: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8"?><soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"><soap:Body>{}</soap:Body></soap:Envelope>"#,
+            body
+        )
+    }
+
+    #[test]
+    fn checked_in() {
+        let xml = envelope(r#"<CheckInFileResponse xmlns="http://schemas.microsoft.com/sharepoint/soap/"><CheckInFileResult>true</CheckInFileResult></CheckInFileResponse>"#);
+        assert_eq!(parse_checkin_result(&xml).unwrap(), CheckinResult::Done(true));
+    }
+
+    #[test]
+    fn not_checked_in() {
+        let xml = envelope(r#"<CheckInFileResponse xmlns="http://schemas.microsoft.com/sharepoint/soap/"><CheckInFileResult>false</CheckInFileResult></CheckInFileResponse>"#);
+        assert_eq!(parse_checkin_result(&xml).unwrap(), CheckinResult::Done(false));
+    }
+
+    #[test]
+    fn soap_fault() {
+        let xml = envelope(
+            r#"<soap:Fault><faultcode>soap:Server</faultcode><faultstring>Exception of type 'Microsoft.SharePoint.SoapServer.SoapServerException' was thrown.</faultstring><detail><errorstring xmlns="http://schemas.microsoft.com/sharepoint/soap/">The file "Shared Documents/a.docx" is not checked out &amp; can't be checked in.</errorstring></detail></soap:Fault>"#,
+        );
+        assert_eq!(
+            parse_checkin_result(&xml).unwrap(),
+            CheckinResult::Fault("The file \"Shared Documents/a.docx\" is not checked out & can't be checked in.".to_string())
+        );
+    }
+
+    #[test]
+    fn missing_result() {
+        assert_eq!(parse_checkin_result("").unwrap(), CheckinResult::Missing);
+        assert_eq!(parse_checkin_result("<html><body>Bad Request</body></html>").unwrap(), CheckinResult::Missing);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::Client;
+use url::Url;
+
+use crate::utils::buildBodyForSOAP::build_body_for_soap;
+use crate::utils::escapeXML::escape_xml;
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
+use crate::utils::xmlElements::parse_elements;
+
+/// Error returned by Copy.asmx for one destination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyError {
+    /// `ErrorCode` of the `<CopyResult>`, e.g. `DestinationInvalid`
+    pub code: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for CopyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[SharepointSharp 'copyFile'] {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for CopyError {}
+
+/// Result of the copy to one destination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyResult {
+    pub destination_url: String,
+    pub result: Result<(), CopyError>,
+}
+
+/// Copy a file to one or several destinations with Copy.asmx `CopyIntoItems`
+///
+/// `source_url` and `dest_urls` can be absolute, or relative to the site URL.
+/// `fields` are (internal name, value) pairs set on the copies. A copy can fail
+/// for some destinations only, so each one gets its own `CopyResult`.
+pub async fn copy_file(
+    source_url: &str,
+    dest_urls: &[&str],
+    fields: &[(String, String)],
+    setup: Option<HashMap<String, String>>,
+) -> Result<Vec<CopyResult>, Box<dyn std::error::Error + Send + Sync>> {
+    if source_url.is_empty() {
+        return Err("[SharepointSharp 'copyFile'] the source URL is required.".into());
+    }
+    if dest_urls.is_empty() {
+        return Err("[SharepointSharp 'copyFile'] at least one destination URL is required.".into());
+    }
+
+    let setup = setup.unwrap_or_default();
+    let site = Url::parse(&discover_url(setup.get("url").map(String::as_str))?)?;
+    let source_url = resolve_url(&site, source_url)?;
+    let dest_urls = dest_urls
+        .iter()
+        .map(|url| resolve_url(&site, url))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Get the content of the source file
+    let client = Client::new();
+    let response = client.get(source_url.clone()).send().await?;
+    if !response.status().is_success() {
+        return Err(format!("[SharepointSharp 'copyFile'] unable to read '{}': {}", source_url, response.status()).into());
+    }
+    let content = response.bytes().await?;
+
+    let soap_body = build_body_for_soap("CopyIntoItems", &copy_into_items_body(&source_url, &dest_urls, fields, &STANDARD.encode(&content)), None);
+    let response = client
+        .post(resolve_url(&site, "_vti_bin/Copy.asmx")?)
+        .header("Content-Type", "text/xml; charset=utf-8")
+        .header("SOAPAction", "http://schemas.microsoft.com/sharepoint/soap/CopyIntoItems")
+        .body(soap_body)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("[SharepointSharp 'copyFile'] the copy failed: {}", response.status()).into());
+    }
+
+    Ok(parse_copy_results(&response.text().await?)?)
+}
+
+fn copy_into_items_body(source_url: &Url, dest_urls: &[Url], fields: &[(String, String)], stream: &str) -> String {
+    let dest_urls: String = dest_urls
+        .iter()
+        .map(|url| format!("<string>{}</string>", escape_xml(url.as_str())))
+        .collect();
+    let fields: String = fields
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                r#"<FieldInformation Type="Text" InternalName="{}" DisplayName="{}" Value="{}"/>"#,
+                escape_xml(name),
+                escape_xml(name),
+                escape_xml(value)
+            )
+        })
+        .collect();
+    format!(
+        "<SourceUrl>{}</SourceUrl><DestinationUrls>{}</DestinationUrls><Fields>{}</Fields><Stream>{}</Stream>",
+        escape_xml(source_url.as_str()),
+        dest_urls,
+        fields,
+        stream
+    )
+}
+
+// Read each <CopyResult ErrorCode="..." ErrorMessage="..." DestinationUrl="..."/>
+fn parse_copy_results(xml: &str) -> Result<Vec<CopyResult>, quick_xml::Error> {
+    parse_elements(xml, "CopyResult", |attrs| {
+        let code = attrs.get("ErrorCode")?;
+        let result = if code == "Success" {
+            Ok(())
+        } else {
+            Err(CopyError {
+                code,
+                message: attrs.get("ErrorMessage")?,
+            })
+        };
+        Ok(CopyResult {
+            destination_url: attrs.get("DestinationUrl")?,
+            result,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn body() {
+        let source = Url::parse("https://contoso.sharepoint.com/sites/x/Docs/a&b.txt").unwrap();
+        let dest = [Url::parse("https://contoso.sharepoint.com/sites/x/Archive/a&b.txt").unwrap()];
+        let fields = [("Title".to_string(), "R&D".to_string())];
+        assert_eq!(
+            copy_into_items_body(&source, &dest, &fields, "SGVsbG8="),
+            "<SourceUrl>https://contoso.sharepoint.com/sites/x/Docs/a&amp;b.txt</SourceUrl>\
+             <DestinationUrls><string>https://contoso.sharepoint.com/sites/x/Archive/a&amp;b.txt</string></DestinationUrls>\
+             <Fields><FieldInformation Type=\"Text\" InternalName=\"Title\" DisplayName=\"Title\" Value=\"R&amp;D\"/></Fields>\
+             <Stream>SGVsbG8=</Stream>"
+        );
+    }
+
+    #[test]
+    fn partial_failure() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <CopyIntoItemsResponse xmlns="http://schemas.microsoft.com/sharepoint/soap/">
+      <CopyIntoItemsResult>0</CopyIntoItemsResult>
+      <Results>
+        <CopyResult ErrorCode="Success" DestinationUrl="https://contoso.sharepoint.com/sites/x/Archive/a.txt" />
+        <CopyResult ErrorCode="DestinationInvalid" ErrorMessage="The Copy web service method must be called on the same domain that contains the destination url." DestinationUrl="https://other.sharepoint.com/a.txt" />
+      </Results>
+    </CopyIntoItemsResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+        let results = parse_copy_results(xml).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].destination_url, "https://contoso.sharepoint.com/sites/x/Archive/a.txt");
+        assert_eq!(results[0].result, Ok(()));
+        assert_eq!(results[1].destination_url, "https://other.sharepoint.com/a.txt");
+        let error = results[1].result.as_ref().unwrap_err();
+        assert_eq!(error.code, "DestinationInvalid");
+        assert!(error.message.starts_with("The Copy web service"));
+    }
+}
@@ -1,22 +1,60 @@
 use std::collections::HashMap;
-use reqwest::{Client, Response};
+use reqwest::{Client, Method};
 use serde_json::Value;
+use url::Url;
 
-struct SharePointClient {
-    url: String,
-    list_id: String,
+use crate::utils::getFormDigest::get_form_digest;
+use crate::utils::preparedRequest::PreparedRequest;
+use crate::utils::resolveURL::resolve_url;
+
+// Values a dry run can't know without calling the server
+const DRY_RUN_ROOT_FOLDER: &str = "{RootFolder}";
+const DRY_RUN_FORM_DIGEST: &str = "{FormDigest}";
+
+pub struct SharePointClient {
+    pub url: String,
+    pub list_id: String,
+}
+
+pub struct FileCreationSetup {
+    pub content: Vec<u8>,
+    pub filename: String,
+    pub extended_fields: String,
+    pub overwrite: bool,
+    pub progress: Box<dyn Fn(u32)>,
+    /// Return the request that would be sent instead of creating the file
+    ///
+    /// Nothing is sent to the server: the REST request is prepared with
+    /// `{RootFolder}` for the root folder of the library and `{FormDigest}` for
+    /// the `X-RequestDigest` header.
+    pub dry_run: bool,
 }
 
-struct FileCreationSetup {
-    content: Vec<u8>,
-    filename: String,
-    extended_fields: String,
-    overwrite: bool,
-    progress: Box<dyn Fn(u32)>,
+#[derive(Debug)]
+pub enum CreateFileResult {
+    File(HashMap<String, String>),
+    DryRun(PreparedRequest),
 }
 
 impl SharePointClient {
-    async fn create_file(&self, setup: FileCreationSetup) -> Result<HashMap<String, String>, String> {
+    /// Create a file in the library `list_id`
+    ///
+    /// ```ignore
+    /// let client = SharePointClient {
+    ///     url: "https://your-sharepoint-url".to_string(),
+    ///     list_id: "your-list-id".to_string(),
+    /// };
+    /// let setup = FileCreationSetup {
+    ///     content: b"Hello World".to_vec(),
+    ///     filename: "example.txt".to_string(),
+    ///     extended_fields: String::new(),
+    ///     overwrite: true,
+    ///     progress: Box::new(|progress| println!("Progress: {}%", progress)),
+    ///     dry_run: false,
+    /// };
+    /// let file = client.create_file(setup).await?;
+    /// ```
+    pub async fn create_file(&self, setup: FileCreationSetup) -> Result<CreateFileResult, String> {
         // Validate input
         if setup.content.is_empty() {
             return Err("[SharepointSharp 'createFile']: the file content is required.".to_string());
@@ -31,6 +69,14 @@ impl SharePointClient {
             return Err("[SharepointSharp 'createFile']: not able to find the URL!".to_string());
         }
 
+        if setup.dry_run {
+            let (folder, filename) = self.process_filename(&setup.filename, DRY_RUN_ROOT_FOLDER);
+            let mut request = self.prepare_create_file_rest(&setup, &folder, &filename, DRY_RUN_FORM_DIGEST)?;
+            // the placeholder is replaced by an encoded path, so keep it readable
+            request.url = request.url.replace(urlencoding::encode(DRY_RUN_ROOT_FOLDER).as_ref(), DRY_RUN_ROOT_FOLDER);
+            return Ok(CreateFileResult::DryRun(request));
+        }
+
         // Get list info
         let info = self.get_list_info().await?;
         let root_folder = info.get("RootFolder").ok_or("RootFolder not found")?;
@@ -38,69 +84,158 @@ impl SharePointClient {
         // Process filename and folder
         let (folder, filename) = self.process_filename(&setup.filename, root_folder);
 
-        // Check if REST API is available
-        if self.has_rest().await {
-            self.create_file_rest(&setup, &folder, &filename).await
-        } else {
-            self.create_file_soap(&setup, &folder, &filename).await
-        }
+        // get_list_info went through the REST API, so it's available
+        self.create_file_rest(&setup, &folder, &filename).await
     }
 
     async fn get_list_info(&self) -> Result<HashMap<String, String>, String> {
-        // Implementation for getting list info
-        todo!()
+        let site = Url::parse(&self.url).map_err(|e| e.to_string())?;
+        let url = resolve_url(&site, &format!("{}/RootFolder?$select=ServerRelativeUrl", list_path(&self.list_id)))
+            .map_err(|e| e.to_string())?;
+
+        let response = Client::new()
+            .get(url)
+            .header("Accept", "application/json;odata=verbose")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("[SharepointSharp 'createFile']: unable to find the library '{}': {}", self.list_id, response.status()));
+        }
+
+        let text = response.text().await.map_err(|e| e.to_string())?;
+        let data: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        let root_folder = data["d"]["ServerRelativeUrl"]
+            .as_str()
+            .ok_or("[SharepointSharp 'createFile']: no ServerRelativeUrl in the response")?;
+        Ok(HashMap::from([("RootFolder".to_string(), root_folder.to_string())]))
     }
 
+    // Split "sub/folder/file.txt" into the folder, under the root folder of the library, and the file name
     fn process_filename(&self, filename: &str, root_folder: &str) -> (String, String) {
-        // Implementation for processing filename
-        todo!()
+        let root_folder = root_folder.trim_end_matches('/');
+        let path = filename.trim_matches('/');
+        match path.rsplit_once('/') {
+            Some((subfolder, name)) => (format!("{}/{}", root_folder, subfolder), name.to_string()),
+            None => (root_folder.to_string(), path.to_string()),
+        }
     }
 
-    async fn has_rest(&self) -> bool {
-        // Implementation to check if REST API is available
-        todo!()
+    fn prepare_create_file_rest(&self, setup: &FileCreationSetup, folder: &str, filename: &str, digest: &str) -> Result<PreparedRequest, String> {
+        let path = format!("_api/web/GetFolderByServerRelativeUrl('{}')/files/add(url='{}',overwrite={})",
+            urlencoding::encode(folder), urlencoding::encode(filename), setup.overwrite);
+        let site = Url::parse(&self.url).map_err(|e| e.to_string())?;
+        let url = resolve_url(&site, &path).map_err(|e| e.to_string())?;
+
+        Ok(PreparedRequest {
+            method: Method::POST,
+            url: url.to_string(),
+            headers: vec![
+                ("Accept".to_string(), "application/json;odata=verbose".to_string()),
+                ("X-RequestDigest".to_string(), digest.to_string()),
+            ],
+            body: setup.content.clone(),
+        })
     }
 
-    async fn create_file_rest(&self, setup: &FileCreationSetup, folder: &str, filename: &str) -> Result<HashMap<String, String>, String> {
+    async fn create_file_rest(&self, setup: &FileCreationSetup, folder: &str, filename: &str) -> Result<CreateFileResult, String> {
         let client = Client::new();
-        let url = format!("{}/_api/web/GetFolderByServerRelativeUrl('{}')/files/add(url='{}',overwrite={})",
-            self.url, urlencoding::encode(folder), urlencoding::encode(filename), setup.overwrite);
+        let digest = get_form_digest(Some(&self.url)).await.map_err(|e| e.to_string())?;
+        let request = self.prepare_create_file_rest(setup, folder, filename, &digest.value)?;
 
-        let response = client.post(&url)
-            .body(setup.content.clone())
+        let response = request.to_request(&client)
             .send()
             .await
             .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("[SharepointSharp 'createFile']: the file wasn't created: {}", response.status()));
+        }
 
-        // Process response and return result
-        todo!()
+        // the properties of the new file (Name, ServerRelativeUrl, ...)
+        let text = response.text().await.map_err(|e| e.to_string())?;
+        let data: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        let file = data["d"]
+            .as_object()
+            .map(|d| {
+                d.iter()
+                    .filter_map(|(name, value)| value.as_str().map(|value| (name.clone(), value.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(CreateFileResult::File(file))
     }
+}
 
-    async fn create_file_soap(&self, setup: &FileCreationSetup, folder: &str, filename: &str) -> Result<HashMap<String, String>, String> {
-        // Implementation for SOAP-based file creation
-        todo!()
+// REST path of a list, from its ID ("{GUID}" or "GUID") or its title
+fn list_path(list_id: &str) -> String {
+    let guid = list_id.trim_start_matches('{').trim_end_matches('}');
+    let is_guid = guid.len() == 36
+        && guid.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if is_guid {
+        format!("_api/web/lists(guid'{}')", guid)
+    } else {
+        format!("_api/web/lists/getbytitle('{}')", urlencoding::encode(&list_id.replace('\'', "''")))
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let client = SharePointClient {
-        url: "https://your-sharepoint-url".to_string(),
-        list_id: "your-list-id".to_string(),
-    };
-
-    let setup = FileCreationSetup {
-        content: vec![/* file content */],
-        filename: "example.txt".to_string(),
-        extended_fields: String::new(),
-        overwrite: true,
-        progress: Box::new(|progress| println!("Progress: {}%", progress)),
-    };
-
-    match client.create_file(setup).await {
-        Ok(file) => println!("File created: {:?}", file),
-        Err(e) => eprintln!("Error: {}", e),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> SharePointClient {
+        SharePointClient {
+            url: "https://contoso.sharepoint.com/sites/x".to_string(),
+            list_id: "Documents".to_string(),
+        }
+    }
+
+    fn setup(filename: &str) -> FileCreationSetup {
+        FileCreationSetup {
+            content: b"Hello World".to_vec(),
+            filename: filename.to_string(),
+            extended_fields: String::new(),
+            overwrite: true,
+            progress: Box::new(|_| {}),
+            dry_run: true,
+        }
+    }
+
+    #[test]
+    fn process_filename() {
+        let client = client();
+        let root = "/sites/x/Shared Documents";
+        assert_eq!(client.process_filename("file.txt", root), (root.to_string(), "file.txt".to_string()));
+        assert_eq!(
+            client.process_filename("/a/b/file.txt", "/sites/x/Shared Documents/"),
+            ("/sites/x/Shared Documents/a/b".to_string(), "file.txt".to_string())
+        );
     }
 
-    Ok(())
-}
\ No newline at end of file
+    #[test]
+    fn dry_run_sends_nothing() {
+        // get_list_info and has_rest would need the server
+        let result = futures::executor::block_on(client().create_file(setup("reports/file.txt"))).unwrap();
+        let CreateFileResult::DryRun(request) = result else {
+            panic!("expected a dry run");
+        };
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(
+            request.url,
+            "https://contoso.sharepoint.com/sites/x/_api/web/GetFolderByServerRelativeUrl('{RootFolder}%2Freports')/files/add(url='file.txt',overwrite=true)"
+        );
+        assert!(request.headers.contains(&("X-RequestDigest".to_string(), "{FormDigest}".to_string())));
+        assert_eq!(request.body, b"Hello World");
+    }
+
+    #[test]
+    fn list_path_by_guid_or_title() {
+        let guid = "d5b0f2c1-6a0e-4c55-9f0b-2a6b1e2f3c4d";
+        assert_eq!(list_path(guid), format!("_api/web/lists(guid'{}')", guid));
+        assert_eq!(list_path(&format!("{{{}}}", guid)), format!("_api/web/lists(guid'{}')", guid));
+        assert_eq!(list_path("Documents"), "_api/web/lists/getbytitle('Documents')");
+        assert_eq!(list_path("Bob's Files"), "_api/web/lists/getbytitle('Bob%27%27s%20Files')");
+    }
+}
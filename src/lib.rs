@@ -0,0 +1,50 @@
+// file names follow the SharepointPlus function names (getURL, escapeXML, ...)
+#![allow(non_snake_case)]
+
+pub mod files {
+    pub mod checkin;
+    pub mod copyFile;
+    pub mod createFile;
+}
+
+pub mod lists {
+    pub mod getAttachments;
+    pub mod getForms;
+    pub mod searchLists;
+}
+
+pub mod modals {
+    pub mod closeModalDialog;
+    pub mod getModalDialog;
+    pub mod notify;
+    pub mod registry;
+    pub mod removeNotify;
+    pub mod resizeModalDialog;
+    pub mod showModalDialog;
+}
+
+pub mod people {
+    pub mod ensureUser;
+    pub mod getUserInfoById;
+    #[allow(clippy::module_inception)]
+    pub mod people;
+}
+
+pub mod site {
+    pub mod getSubwebs;
+    pub mod webInfo;
+}
+
+pub mod utils {
+    pub mod batchBuilder;
+    pub mod buildBodyForSOAP;
+    pub mod caml;
+    pub mod escapeXML;
+    pub mod formatField;
+    pub mod getFormDigest;
+    pub mod getURL;
+    pub mod preparedRequest;
+    pub mod resolveURL;
+    pub mod toSPDate;
+    pub mod xmlElements;
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+use quick_xml::escape::unescape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::Client;
+use url::Url;
+
+use crate::utils::buildBodyForSOAP::build_body_for_soap;
+use crate::utils::escapeXML::escape_xml;
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
+
+// Number of GetAttachmentCollection requests sent at the same time
+const ATTACHMENTS_CONCURRENCY: usize = 5;
+
+/// Get the attachment URLs of several items of a list with Lists.asmx `GetAttachmentCollection`
+///
+/// One request is sent per item, a few at a time. To skip the items without
+/// attachments, only pass the IDs whose `Attachments` field is `"1"` in a
+/// previous `get`.
+pub async fn get_attachments_bulk(
+    list_name: &str,
+    item_ids: &[u32],
+    setup: Option<HashMap<String, String>>,
+) -> Result<HashMap<u32, Vec<String>>, Box<dyn std::error::Error + Send + Sync>> {
+    if list_name.is_empty() {
+        return Err("[SharepointSharp 'getAttachment'] the list name is required.".into());
+    }
+
+    let setup = setup.unwrap_or_default();
+    let url = resolve_url(&Url::parse(&discover_url(setup.get("url").map(String::as_str))?)?, "_vti_bin/Lists.asmx")?;
+    let client = Client::new();
+
+    let mut item_ids = item_ids.to_vec();
+    item_ids.sort_unstable();
+    item_ids.dedup();
+
+    stream::iter(item_ids)
+        .map(|id| {
+            let client = client.clone();
+            let url = url.clone();
+            async move {
+                let soap_body = build_body_for_soap(
+                    "GetAttachmentCollection",
+                    &format!("<listName>{}</listName><listItemID>{}</listItemID>", escape_xml(list_name), id),
+                    None,
+                );
+                let response = client
+                    .post(url)
+                    .header("Content-Type", "text/xml; charset=utf-8")
+                    .header("SOAPAction", "http://schemas.microsoft.com/sharepoint/soap/GetAttachmentCollection")
+                    .body(soap_body)
+                    .send()
+                    .await?;
+                if !response.status().is_success() {
+                    return Err(format!("[SharepointSharp 'getAttachment'] unable to get the attachments of item {}: {}", id, response.status()).into());
+                }
+                Ok((id, parse_attachments(&response.text().await?)?))
+            }
+        })
+        .buffer_unordered(ATTACHMENTS_CONCURRENCY)
+        .try_collect()
+        .await
+}
+
+fn parse_attachments(xml: &str) -> Result<Vec<String>, quick_xml::Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut attachments = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) if e.local_name().as_ref() == b"Attachment" => {
+                let text = reader.read_text(e.name())?;
+                attachments.push(unescape(&text)?.trim().to_string());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(attachments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attachments() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <GetAttachmentCollectionResponse xmlns="http://schemas.microsoft.com/sharepoint/soap/">
+      <GetAttachmentCollectionResult>
+        <Attachments>
+          <Attachment>https://contoso.sharepoint.com/sites/x/Lists/Tasks/Attachments/1/spec.docx</Attachment>
+          <Attachment>https://contoso.sharepoint.com/sites/x/Lists/Tasks/Attachments/1/R&amp;D.pdf</Attachment>
+        </Attachments>
+      </GetAttachmentCollectionResult>
+    </GetAttachmentCollectionResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+        assert_eq!(
+            parse_attachments(xml).unwrap(),
+            [
+                "https://contoso.sharepoint.com/sites/x/Lists/Tasks/Attachments/1/spec.docx",
+                "https://contoso.sharepoint.com/sites/x/Lists/Tasks/Attachments/1/R&D.pdf",
+            ]
+        );
+        assert!(parse_attachments("<Attachments />").unwrap().is_empty());
+    }
+}
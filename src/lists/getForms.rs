@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use reqwest::Client;
+use url::Url;
+
+use crate::utils::buildBodyForSOAP::build_body_for_soap;
+use crate::utils::escapeXML::escape_xml;
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
+use crate::utils::xmlElements::parse_elements;
+
+/// A form of a list, from a `<Form>` of `GetFormCollection`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormInfo {
+    /// `DisplayForm`, `EditForm` or `NewForm`
+    pub form_type: String,
+    /// Site relative URL, e.g. `Lists/Tasks/EditForm.aspx`
+    pub url: String,
+}
+
+/// Get the forms of a list with Forms.asmx `GetFormCollection`
+///
+/// `list_name` is the title or the GUID of the list.
+pub async fn get_forms(list_name: &str, setup: Option<HashMap<String, String>>) -> Result<Vec<FormInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    if list_name.is_empty() {
+        return Err("[SharepointSharp 'getForms'] the list name is required.".into());
+    }
+
+    let setup = setup.unwrap_or_default();
+    let site = Url::parse(&discover_url(setup.get("url").map(String::as_str))?)?;
+
+    let soap_body = build_body_for_soap("GetFormCollection", &format!("<listName>{}</listName>", escape_xml(list_name)), None);
+    let response = Client::new()
+        .post(resolve_url(&site, "_vti_bin/Forms.asmx")?)
+        .header("Content-Type", "text/xml; charset=utf-8")
+        .header("SOAPAction", "http://schemas.microsoft.com/sharepoint/soap/GetFormCollection")
+        .body(soap_body)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("[SharepointSharp 'getForms'] unable to get the forms of '{}': {}", list_name, response.status()).into());
+    }
+
+    Ok(parse_forms(&response.text().await?)?)
+}
+
+fn parse_forms(xml: &str) -> Result<Vec<FormInfo>, quick_xml::Error> {
+    parse_elements(xml, "Form", |attrs| {
+        Ok(FormInfo {
+            form_type: attrs.get("Type")?,
+            url: attrs.get("Url")?,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forms() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <GetFormCollectionResponse xmlns="http://schemas.microsoft.com/sharepoint/soap/">
+      <GetFormCollectionResult>
+        <Forms>
+          <Form Type="DisplayForm" Url="Lists/Tasks/DispForm.aspx" />
+          <Form Type="EditForm" Url="Lists/Tasks/EditForm.aspx" />
+          <Form Type="NewForm" Url="Lists/Tasks/NewForm.aspx" />
+        </Forms>
+      </GetFormCollectionResult>
+    </GetFormCollectionResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+        let forms = parse_forms(xml).unwrap();
+        let forms: Vec<(&str, &str)> = forms.iter().map(|f| (f.form_type.as_str(), f.url.as_str())).collect();
+        assert_eq!(
+            forms,
+            [
+                ("DisplayForm", "Lists/Tasks/DispForm.aspx"),
+                ("EditForm", "Lists/Tasks/EditForm.aspx"),
+                ("NewForm", "Lists/Tasks/NewForm.aspx"),
+            ]
+        );
+    }
+}
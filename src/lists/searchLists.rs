@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use reqwest::Client;
+use serde_json::Value;
+use url::Url;
+
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
+
+/// Search items across lists with the Search REST API (`_api/search/query`)
+///
+/// `query` is a KQL query, e.g. `ContentType:Task AND Status:Open`. With a
+/// `scope` (a URL, e.g. `https://contoso.sharepoint.com/sites/x/Lists/Tasks`)
+/// only the results under it are returned. Each row maps the managed property
+/// names (`Title`, `Path`, ...) to their values, empty when null.
+pub async fn search_lists(
+    query: &str,
+    scope: Option<&str>,
+    setup: Option<HashMap<String, String>>,
+) -> Result<Vec<HashMap<String, String>>, Box<dyn std::error::Error + Send + Sync>> {
+    if query.trim().is_empty() {
+        return Err("[SharepointSharp 'searchLists'] the query is required.".into());
+    }
+
+    let setup = setup.unwrap_or_default();
+    let site = Url::parse(&discover_url(setup.get("url").map(String::as_str))?)?;
+
+    let response = Client::new()
+        .get(resolve_url(&site, &search_path(query, scope))?)
+        .header("Accept", "application/json;odata=verbose")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("[SharepointSharp 'searchLists'] the search failed: {}", response.status()).into());
+    }
+
+    let data: Value = serde_json::from_str(&response.text().await?)?;
+    Ok(parse_search_rows(&data))
+}
+
+fn search_path(query: &str, scope: Option<&str>) -> String {
+    let query = match scope {
+        Some(scope) => format!("({}) Path:\"{}\"", query, scope.trim_end_matches('/')),
+        None => query.to_string(),
+    };
+    // a string parameter of the URL is quoted, so its quotes are doubled
+    format!("_api/search/query?querytext='{}'", urlencoding::encode(&query.replace('\'', "''")))
+}
+
+// Rows of the relevant results table, each a list of { Key, Value } cells
+fn parse_search_rows(data: &Value) -> Vec<HashMap<String, String>> {
+    let rows = &data["d"]["query"]["PrimaryQueryResult"]["RelevantResults"]["Table"]["Rows"]["results"];
+    rows.as_array()
+        .map(|rows| {
+            rows.iter()
+                .map(|row| {
+                    row["Cells"]["results"]
+                        .as_array()
+                        .map(|cells| {
+                            cells
+                                .iter()
+                                .filter_map(|cell| {
+                                    let key = cell["Key"].as_str()?;
+                                    Some((key.to_string(), cell["Value"].as_str().unwrap_or_default().to_string()))
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn path() {
+        assert_eq!(search_path("Status:Open", None), "_api/search/query?querytext='Status%3AOpen'");
+        assert_eq!(
+            search_path("Title:O'Brien", Some("https://contoso.sharepoint.com/sites/x/Lists/Tasks/")),
+            "_api/search/query?querytext='%28Title%3AO%27%27Brien%29%20Path%3A%22https%3A%2F%2Fcontoso.sharepoint.com%2Fsites%2Fx%2FLists%2FTasks%22'"
+        );
+    }
+
+    #[test]
+    fn rows() {
+        let data = json!({ "d": { "query": { "PrimaryQueryResult": { "RelevantResults": { "Table": { "Rows": { "results": [
+            { "Cells": { "results": [
+                { "Key": "Title", "Value": "Task 1", "ValueType": "Edm.String" },
+                { "Key": "Path", "Value": "https://contoso.sharepoint.com/sites/x/Lists/Tasks/DispForm.aspx?ID=1", "ValueType": "Edm.String" },
+                { "Key": "Author", "Value": null, "ValueType": "Null" }
+            ] } },
+            { "Cells": { "results": [{ "Key": "Title", "Value": "Doc", "ValueType": "Edm.String" }] } }
+        ] } } } } } } });
+        let rows = parse_search_rows(&data);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["Title"], "Task 1");
+        assert_eq!(rows[0]["Path"], "https://contoso.sharepoint.com/sites/x/Lists/Tasks/DispForm.aspx?ID=1");
+        assert_eq!(rows[0]["Author"], "");
+        assert_eq!(rows[1], HashMap::from([("Title".to_string(), "Doc".to_string())]));
+
+        // no result
+        assert!(parse_search_rows(&json!({ "d": { "query": {} } })).is_empty());
+    }
+}
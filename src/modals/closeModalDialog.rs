@@ -1,65 +1,60 @@
-use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+use js_sys::{Object, Reflect};
 
-// Placeholder types to represent JavaScript objects
-type ModalDialog = HashMap<String, Box<dyn std::any::Any>>;
-type DialogResult = HashMap<String, Box<dyn std::any::Any>>;
+use super::registry::{self, OpenDialog};
 
-// Placeholder for SP.SOD.executeOrDelayUntilScriptLoaded
-fn execute_or_delay_until_script_loaded<F: Fn()>(f: F, _script_name: &str) {
-    f(); // In Rust, we just call the function directly
-}
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["SP", "UI", "ModalDialog"], js_name = commonModalDialogClose)]
+    fn common_modal_dialog_close(dialog_result: i32, return_value: &JsValue);
 
-// Placeholder for SP.UI.ModalDialog.commonModalDialogClose
-fn common_modal_dialog_close(_dialog_result: &DialogResult, _return_value: &Box<dyn std::any::Any>) {
-    // Implementation would go here
+    #[wasm_bindgen(js_namespace = ["SP", "SOD"], js_name = executeOrDelayUntilScriptLoaded)]
+    fn execute_or_delay_until_script_loaded(f: &JsValue, script_name: &str) -> bool;
 }
 
-pub fn close_modal_dialog(dialog_result: DialogResult, return_value: Box<dyn std::any::Any>) -> bool {
-    let fct = || {
-        if let Some(dialog_type) = dialog_result.get("type") {
-            if let Some(dialog_type_str) = dialog_type.downcast_ref::<String>() {
-                if dialog_type_str == "modalDialog" {
-                    let mut md = HashMap::new();
-                    md.insert("id".to_string(), dialog_result.get("id").unwrap().clone());
-                    md.insert("dialogResult".to_string(), Box::new(return_value.clone()));
-                    md.insert("returnValue".to_string(), Box::new(()));
-                    md.insert("type".to_string(), Box::new("closeModalDialog".to_string()));
+/// What to close: one of our modal dialogs, or the current SharePoint dialog
+/// with a `SP.UI.DialogResult` value (-1 invalid, 0 cancel, 1 OK)
+pub enum DialogResult {
+    ModalDialog(OpenDialog),
+    Value(i32),
+}
 
-                    if let Some(modal) = dialog_result.get("modal") {
-                        if let Some(modal_obj) = modal.downcast_ref::<ModalDialog>() {
-                            if let Some(close_fn) = modal_obj.get("close") {
-                                if let Some(close) = close_fn.downcast_ref::<Box<dyn Fn(&ModalDialog)>>() {
-                                    close(&md);
-                                }
-                            }
-                        }
-                    }
+// Object passed to `modal.close()` and to `dialogReturnValueCallback`
+fn closed_dialog(id: &str, return_value: &JsValue) -> JsValue {
+    let md = Object::new();
+    let _ = Reflect::set(&md, &"id".into(), &id.into());
+    let _ = Reflect::set(&md, &"dialogResult".into(), return_value);
+    let _ = Reflect::set(&md, &"returnValue".into(), &JsValue::UNDEFINED);
+    let _ = Reflect::set(&md, &"type".into(), &"closeModalDialog".into());
+    md.into()
+}
 
-                    if let Some(options) = dialog_result.get("options") {
-                        if let Some(options_obj) = options.downcast_ref::<HashMap<String, Box<dyn std::any::Any>>>() {
-                            if let Some(wait) = options_obj.get("wait") {
-                                if let Some(wait_value) = wait.downcast_ref::<bool>() {
-                                    if *wait_value {
-                                        if let Some(callback) = options_obj.get("dialogReturnValueCallback") {
-                                            if let Some(callback_fn) = callback.downcast_ref::<Box<dyn Fn(&ModalDialog, &Box<dyn std::any::Any>)>>() {
-                                                callback_fn(&md, &return_value);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    // Placeholder for window.top._SP_MODALDIALOG
-                    // In a real Rust application, you'd need to handle this differently
-                    common_modal_dialog_close(&dialog_result, &return_value);
+/// Close a modal dialog
+///
+/// Returns `false` so it can be used directly in an `onclick` handler
+pub fn close_modal_dialog(dialog_result: DialogResult, return_value: JsValue) -> bool {
+    let fct = move || match dialog_result {
+        DialogResult::ModalDialog(dialog) => {
+            registry::unregister(&dialog.id);
+            let md = closed_dialog(&dialog.id, &return_value);
+            dialog.modal.close(&md);
+
+            // with "wait" SharePoint doesn't trigger the callback by itself
+            if dialog.options.wait {
+                if let Some(callback) = &dialog.options.dialog_return_value_callback {
+                    let _ = callback.call2(&JsValue::NULL, &md, &return_value);
                 }
             }
         }
+        DialogResult::Value(value) => {
+            // SharePoint closes the topmost dialog, which may not be one of ours; ours
+            // are unregistered by the dialogReturnValueCallback installed in show()
+            common_modal_dialog_close(value, &return_value);
+        }
     };
 
-    execute_or_delay_until_script_loaded(fct, "sp.ui.dialog.js");
+    let fct = Closure::once_into_js(fct);
+    execute_or_delay_until_script_loaded(&fct, "sp.ui.dialog.js");
 
     false
-}
\ No newline at end of file
+}
@@ -1,18 +1,44 @@
+use std::cell::RefCell;
 use std::collections::VecDeque;
-use std::time::{Duration, SystemTime};
-use std::sync::{Mutex, Arc};
-use std::thread;
-
-struct Options {
-    timeout: u64,
-    override_all: bool,
-    override: bool,
-    override_sticky: bool,
-    sticky: bool,
-    name: u128,
-    after: Box<dyn Fn() + Send>,
-    fake: bool,
-    ignore_queue: bool,
+use std::sync::Arc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::window;
+
+use super::removeNotify::{remove_notify, RemoveOptions};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["SP", "UI", "Notify"], js_name = addNotification)]
+    fn add_notification(html: &str, sticky: bool) -> String;
+
+    #[wasm_bindgen(js_namespace = ["SP", "UI", "Notify"], js_name = removeNotification)]
+    pub(super) fn remove_notification(id: &str);
+
+    #[wasm_bindgen(js_namespace = ["SP", "SOD"], js_name = executeOrDelayUntilScriptLoaded)]
+    fn execute_or_delay_until_script_loaded(f: &JsValue, script_name: &str) -> bool;
+}
+
+/// Callback receiving the notification name and whether its timeout elapsed
+pub type AfterCallback = Arc<dyn Fn(&str, bool) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Options {
+    /// Seconds before the notification is removed (ignored when sticky)
+    pub timeout: u32,
+    /// Remove all the existing notifications first
+    pub override_all: bool,
+    /// With `override_all`, also remove the sticky ones
+    pub override_sticky: bool,
+    /// Remove the last notification first
+    pub override_last: bool,
+    pub sticky: bool,
+    pub name: String,
+    /// Called once the notification has been removed
+    pub after: AfterCallback,
+    pub fake: bool,
+    pub ignore_queue: bool,
 }
 
 impl Default for Options {
@@ -20,27 +46,35 @@ impl Default for Options {
         Options {
             timeout: 5,
             override_all: false,
-            override: false,
             override_sticky: true,
+            override_last: false,
             sticky: false,
-            name: SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis(),
-            after: Box::new(|| {}),
+            name: js_sys::Date::now().to_string(),
+            after: Arc::new(|_, _| {}),
             fake: false,
             ignore_queue: false,
         }
     }
 }
 
-struct Notify {
-    queue: VecDeque<(String, Options)>,
-    notify_list: Vec<NotifyItem>,
-    notify_ready: bool,
+// Notifications to remove before showing a new one
+#[derive(Debug, PartialEq, Eq)]
+enum Override {
+    All { include_sticky: bool },
+    Last(String),
+}
+
+pub(super) struct NotifyItem {
+    pub(super) name: String,
+    pub(super) id: String,
+    pub(super) options: Options,
 }
 
-struct NotifyItem {
-    name: u128,
-    id: u32,
-    options: Options,
+// Notifications waiting for sp.js, and the ones currently displayed
+pub(super) struct Notify {
+    pub(super) queue: VecDeque<(String, Options)>,
+    pub(super) notify_list: Vec<NotifyItem>,
+    pub(super) notify_ready: bool,
 }
 
 impl Notify {
@@ -52,94 +86,196 @@ impl Notify {
         }
     }
 
-    fn add_notification(&mut self, message: String, options: Options) {
-        if !self.notify_ready {
-            self.queue.push_back((message, options));
-            self.load_dependencies();
+    // The queued notifications in the order they were sent, to show them now
+    fn take_queue(&mut self) -> Vec<(String, Options)> {
+        self.queue
+            .drain(..)
+            .map(|(message, mut options)| {
+                options.ignore_queue = true;
+                (message, options)
+            })
+            .collect()
+    }
+
+    // What `override_all`/`override_last` remove, when something is displayed
+    fn override_for(&self, options: &Options) -> Option<Override> {
+        let last = self.notify_list.last()?;
+        if options.override_all {
+            Some(Override::All { include_sticky: options.override_sticky })
+        } else if options.override_last {
+            Some(Override::Last(last.name.clone()))
         } else {
-            self.process_queue(options.ignore_queue);
-            if !options.fake {
-                self.handle_override(&options);
-                let id = self.display_notification(&message);
-                self.notify_list.push(NotifyItem {
-                    name: options.name,
-                    id,
-                    options,
-                });
-                self.setup_timeout(&options);
-            }
+            None
         }
     }
 
-    fn load_dependencies(&mut self) {
-        // Simulate loading dependencies
-        self.notify_ready = true;
-        self.process_queue(false);
+    pub(super) fn take_all(&mut self, include_sticky: bool) -> Vec<NotifyItem> {
+        let (kept, removed) = self
+            .notify_list
+            .drain(..)
+            .partition(|item| !include_sticky && item.options.sticky);
+        self.notify_list = kept;
+        removed
     }
 
-    fn process_queue(&mut self, ignore_queue: bool) {
-        if !ignore_queue {
-            while let Some((msg, mut opt)) = self.queue.pop_front() {
-                opt.ignore_queue = true;
-                self.add_notification(msg, opt);
-            }
-        }
+    pub(super) fn take_by_name(&mut self, name: &str) -> Option<NotifyItem> {
+        let index = self.notify_list.iter().position(|item| item.name == name)?;
+        Some(self.notify_list.remove(index))
     }
+}
 
-    fn handle_override(&mut self, options: &Options) {
-        if self.notify_list.is_empty() {
-            return;
-        }
+thread_local! {
+    pub(super) static SP_NOTIFY: RefCell<Notify> = RefCell::new(Notify::new());
+}
 
-        if options.override_all {
-            self.remove_notify(true, options.override_sticky);
-        } else if options.override {
-            if let Some(last) = self.notify_list.last() {
-                self.remove_notify_by_name(last.name);
-            }
+pub(super) fn set_timeout<F: FnOnce() + 'static>(f: F, millis: i32) {
+    let f = Closure::once_into_js(f);
+    let _ = window()
+        .unwrap()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(f.unchecked_ref(), millis);
+}
+
+/// Show a notification at the top right of the page
+///
+/// Notifications sent before sp.js is loaded are queued and shown in order once it is.
+pub fn notify(message: &str, options: Options) {
+    let ready = SP_NOTIFY.with(|n| n.borrow().notify_ready);
+    if !ready {
+        SP_NOTIFY.with(|n| n.borrow_mut().queue.push_back((message.to_string(), options)));
+        let fct = Closure::once_into_js(|| {
+            SP_NOTIFY.with(|n| n.borrow_mut().notify_ready = true);
+            notify("fake", Options { fake: true, ..Default::default() });
+        });
+        execute_or_delay_until_script_loaded(&fct, "sp.js");
+        return;
+    }
+
+    if !options.ignore_queue {
+        let queue = SP_NOTIFY.with(|n| n.borrow_mut().take_queue());
+        for (message, options) in queue {
+            notify(&message, options);
         }
     }
 
-    fn display_notification(&self, _message: &str) -> u32 {
-        // Simulate adding a notification and returning its ID
-        42
+    if options.fake {
+        return;
     }
 
-    fn remove_notify(&mut self, all: bool, include_sticky: bool) {
-        if all {
-            self.notify_list.retain(|item| !include_sticky || item.options.sticky);
+    match SP_NOTIFY.with(|n| n.borrow().override_for(&options)) {
+        Some(Override::All { include_sticky }) => {
+            remove_notify(
+                None,
+                Some(RemoveOptions {
+                    all: true,
+                    include_sticky,
+                    ..Default::default()
+                }),
+            );
+        }
+        Some(Override::Last(last)) => {
+            remove_notify(Some(&last), None);
         }
+        None => {}
     }
 
-    fn remove_notify_by_name(&mut self, name: u128) {
-        self.notify_list.retain(|item| item.name != name);
+    let id = add_notification(message, options.sticky);
+    let name = options.name.clone();
+    let sticky = options.sticky;
+    let timeout = options.timeout;
+    SP_NOTIFY.with(|n| n.borrow_mut().notify_list.push(NotifyItem { name: name.clone(), id, options }));
+
+    if !sticky {
+        let fct = move || {
+            remove_notify(
+                Some(&name),
+                Some(RemoveOptions {
+                    timeout: true,
+                    ..Default::default()
+                }),
+            )
+        };
+        set_timeout(fct, timeout as i32 * 1000);
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    // Options::default() reads the JS clock, not available in native tests
+    pub(in crate::modals) fn options(name: &str, sticky: bool) -> Options {
+        Options {
+            timeout: 5,
+            override_all: false,
+            override_sticky: true,
+            override_last: false,
+            sticky,
+            name: name.to_string(),
+            after: Arc::new(|_, _| {}),
+            fake: false,
+            ignore_queue: false,
+        }
     }
 
-    fn setup_timeout(&self, options: &Options) {
-        if !options.sticky {
-            let name = options.name;
-            let timeout = options.timeout;
-            let after = options.after.clone();
-            thread::spawn(move || {
-                thread::sleep(Duration::from_secs(timeout));
-                (after)();
-                // Remove the notification after the timeout
+    pub(in crate::modals) fn notify_list(items: &[(&str, bool)]) -> Notify {
+        let mut notify = Notify::new();
+        for (index, (name, sticky)) in items.iter().enumerate() {
+            notify.notify_list.push(NotifyItem {
+                name: name.to_string(),
+                id: format!("notif{}", index),
+                options: options(name, *sticky),
             });
         }
+        notify
     }
-}
 
-fn main() {
-    let notify = Arc::new(Mutex::new(Notify::new()));
-    let options = Options {
-        timeout: 10,
-        ..Default::default()
-    };
-
-    let message = "This is a test notification".to_string();
-    let notify_clone = Arc::clone(&notify);
-    let _ = thread::spawn(move || {
-        let mut notify = notify_clone.lock().unwrap();
-        notify.add_notification(message, options);
-    });
+    #[test]
+    fn queue_is_shown_in_order() {
+        let mut notify = Notify::new();
+        for name in ["first", "second", "third"] {
+            notify.queue.push_back((format!("message {}", name), options(name, false)));
+        }
+
+        let queue = notify.take_queue();
+        let messages: Vec<&str> = queue.iter().map(|(message, _)| message.as_str()).collect();
+        assert_eq!(messages, ["message first", "message second", "message third"]);
+        // shown with ignore_queue so they don't process the queue again
+        assert!(queue.iter().all(|(_, options)| options.ignore_queue));
+        assert!(notify.queue.is_empty());
+        assert!(notify.take_queue().is_empty());
+    }
+
+    #[test]
+    fn override_all() {
+        let notify = notify_list(&[("a", false), ("b", true)]);
+        let mut new = options("new", false);
+        new.override_all = true;
+        assert_eq!(notify.override_for(&new), Some(Override::All { include_sticky: true }));
+        new.override_sticky = false;
+        assert_eq!(notify.override_for(&new), Some(Override::All { include_sticky: false }));
+        // override_all wins over override_last
+        new.override_last = true;
+        assert_eq!(notify.override_for(&new), Some(Override::All { include_sticky: false }));
+    }
+
+    #[test]
+    fn override_last() {
+        let notify = notify_list(&[("a", false), ("b", true)]);
+        let mut new = options("new", false);
+        new.override_last = true;
+        assert_eq!(notify.override_for(&new), Some(Override::Last("b".to_string())));
+    }
+
+    #[test]
+    fn no_override() {
+        let notify = notify_list(&[("a", false)]);
+        assert_eq!(notify.override_for(&options("new", false)), None);
+
+        // nothing displayed, nothing to remove
+        let notify = notify_list(&[]);
+        let mut new = options("new", false);
+        new.override_all = true;
+        new.override_last = true;
+        assert_eq!(notify.override_for(&new), None);
+    }
 }
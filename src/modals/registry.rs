@@ -0,0 +1,85 @@
+use std::cell::RefCell;
+
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+use super::closeModalDialog::{close_modal_dialog, DialogResult};
+use super::resizeModalDialog::{resize_modal_dialog, ResizeOptions};
+
+#[wasm_bindgen]
+extern "C" {
+    // SP.UI.ModalDialog instance as returned by SP.UI.ModalDialog.showModalDialog
+    #[derive(Clone)]
+    #[wasm_bindgen(js_namespace = ["SP", "UI"], js_name = ModalDialog)]
+    pub type SpModalDialog;
+
+    #[wasm_bindgen(method)]
+    pub fn close(this: &SpModalDialog, dialog_result: &JsValue);
+}
+
+/// Options the dialog was opened with that matter when closing it
+#[derive(Clone, Default)]
+pub struct DialogOptions {
+    pub wait: bool,
+    pub dialog_return_value_callback: Option<Function>,
+}
+
+/// A dialog opened with `show_modal_dialog`
+#[derive(Clone)]
+pub struct OpenDialog {
+    pub id: String,
+    pub modal: SpModalDialog,
+    pub options: DialogOptions,
+}
+
+impl OpenDialog {
+    /// Id of the dialog element in the page
+    pub fn frame_id(&self) -> String {
+        format!("sp_frame_{}", self.id)
+    }
+
+    pub fn close(&self, return_value: JsValue) -> bool {
+        close_modal_dialog(DialogResult::ModalDialog(self.clone()), return_value)
+    }
+
+    pub fn resize(&self, width: Option<i32>, height: Option<i32>) {
+        resize_modal_dialog(ResizeOptions {
+            id: Some(self.id.clone()),
+            width,
+            height,
+        });
+    }
+}
+
+// Replacement for window.top._SP_MODALDIALOG: stack of the dialogs opened with
+// show_modal_dialog, pushed on show and removed on close, so the last one is
+// the topmost dialog (WASM is single threaded)
+thread_local! {
+    static SP_MODALDIALOG: RefCell<Vec<OpenDialog>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Keep only the alphanumeric characters of an id, as used in `sp_frame_{id}`
+pub fn sanitize_id(id: &str) -> String {
+    id.chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+pub fn register(dialog: OpenDialog) {
+    SP_MODALDIALOG.with(|md| md.borrow_mut().push(dialog));
+}
+
+pub fn unregister(id: &str) -> Option<OpenDialog> {
+    SP_MODALDIALOG.with(|md| {
+        let mut md = md.borrow_mut();
+        let index = md.iter().position(|dialog| dialog.id == id)?;
+        Some(md.remove(index))
+    })
+}
+
+pub fn find(id: &str) -> Option<OpenDialog> {
+    SP_MODALDIALOG.with(|md| md.borrow().iter().find(|dialog| dialog.id == id).cloned())
+}
+
+/// The most recently opened dialog still registered
+pub fn last() -> Option<OpenDialog> {
+    SP_MODALDIALOG.with(|md| md.borrow().last().cloned())
+}
@@ -1,74 +1,93 @@
-use std::collections::VecDeque;
-use std::time::Duration;
-use std::thread;
+use super::notify::{remove_notification, set_timeout, NotifyItem, SP_NOTIFY};
 
-#[derive(Clone)]
-struct Notification {
-    id: String,
-    name: String,
-    options: NotificationOptions,
+#[derive(Clone, Copy)]
+pub struct RemoveOptions {
+    /// Remove all the notifications instead of the named one
+    pub all: bool,
+    /// With `all`, also remove the sticky notifications
+    pub include_sticky: bool,
+    /// Passed to the `after` callback: the removal comes from the notification timeout
+    pub timeout: bool,
 }
 
-#[derive(Clone)]
-struct NotificationOptions {
-    sticky: bool,
-    after: fn(&str, bool),
-}
-
-struct RemoveOptions {
-    all: bool,
-    include_sticky: bool,
-    timeout: bool,
+impl Default for RemoveOptions {
+    fn default() -> Self {
+        RemoveOptions {
+            all: false,
+            include_sticky: true,
+            timeout: false,
+        }
+    }
 }
 
-static mut SP_NOTIFY: Option<VecDeque<Notification>> = None;
-static mut SP_NOTIFY_READY: bool = false;
-
-fn remove_notify(name: Option<&str>, options: Option<RemoveOptions>) -> Result<(), String> {
-    let options = options.unwrap_or(RemoveOptions {
-        all: false,
-        include_sticky: true,
-        timeout: false,
-    });
+/// Remove a notification shown with `notify`, or all of them with `options.all`
+pub fn remove_notify(name: Option<&str>, options: Option<RemoveOptions>) {
+    let options = options.unwrap_or_default();
 
     // Make sure we are ready
-    unsafe {
-        if !SP_NOTIFY_READY && SP_NOTIFY.as_ref().unwrap().len() > 0 {
-            thread::sleep(Duration::from_millis(150));
-            return remove_notify(name, Some(options));
-        }
+    let waiting = SP_NOTIFY.with(|n| {
+        let n = n.borrow();
+        !n.notify_ready && !n.queue.is_empty()
+    });
+    if waiting {
+        let name = name.map(|name| name.to_string());
+        set_timeout(move || remove_notify(name.as_deref(), Some(options)), 150);
+        return;
     }
 
-    unsafe {
+    let removed: Vec<NotifyItem> = SP_NOTIFY.with(|n| {
+        let mut n = n.borrow_mut();
         if options.all {
-            let mut a = VecDeque::new();
-            while let Some(notif) = SP_NOTIFY.as_mut().unwrap().pop_front() {
-                if !options.include_sticky && notif.options.sticky {
-                    a.push_back(notif);
-                } else {
-                    // Simulating SP.UI.Notify.removeNotification
-                    println!("Removing notification: {}", notif.id);
-                    let after_fn = notif.options.after;
-                    thread::spawn(move || {
-                        thread::sleep(Duration::from_millis(150));
-                        after_fn(&notif.name, false);
-                    });
-                }
-            }
-            *SP_NOTIFY.as_mut().unwrap() = a;
-        } else if let Some(name) = name {
-            if let Some(index) = SP_NOTIFY.as_ref().unwrap().iter().position(|n| n.name == name) {
-                let notif = SP_NOTIFY.as_mut().unwrap().remove(index).unwrap();
-                // Simulating SP.UI.Notify.removeNotification
-                println!("Removing notification: {}", notif.id);
-                let after_fn = notif.options.after;
-                thread::spawn(move || {
-                    thread::sleep(Duration::from_millis(150));
-                    after_fn(&notif.name, options.timeout);
-                });
-            }
+            n.take_all(options.include_sticky)
+        } else {
+            name.and_then(|name| n.take_by_name(name)).into_iter().collect()
         }
+    });
+
+    for notif in removed {
+        remove_notification(&notif.id);
+        // leave SharePoint the time to remove it from the page
+        let after = notif.options.after;
+        let name = notif.name;
+        set_timeout(move || after(&name, options.timeout), 150);
     }
+}
 
-    Ok(())
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use crate::modals::notify::tests::notify_list;
+    use crate::modals::notify::Notify;
+
+    fn names(notify: &Notify) -> Vec<&str> {
+        notify.notify_list.iter().map(|item| item.name.as_str()).collect()
+    }
+
+    #[test]
+    fn remove_all_including_sticky() {
+        let mut notify = notify_list(&[("a", false), ("b", true), ("c", false)]);
+        let removed = notify.take_all(true);
+        assert_eq!(removed.len(), 3);
+        assert!(notify.notify_list.is_empty());
+    }
+
+    #[test]
+    fn remove_all_keeping_sticky() {
+        let mut notify = notify_list(&[("a", true), ("b", false), ("c", true), ("d", false)]);
+        let removed: Vec<String> = notify.take_all(false).into_iter().map(|item| item.name).collect();
+        assert_eq!(removed, ["b", "d"]);
+        assert_eq!(names(&notify), ["a", "c"]);
+    }
+
+    #[test]
+    fn remove_by_name() {
+        let mut notify = notify_list(&[("a", false), ("b", true), ("c", false)]);
+        // sticky ones can be removed by name
+        let removed = notify.take_by_name("b").unwrap();
+        assert_eq!(removed.id, "notif1");
+        assert_eq!(names(&notify), ["a", "c"]);
+
+        assert!(notify.take_by_name("b").is_none());
+        assert!(notify.take_by_name("unknown").is_none());
+        assert_eq!(names(&notify), ["a", "c"]);
+    }
+}
@@ -1,6 +1,8 @@
 use web_sys::{window, Element, HtmlElement};
 use wasm_bindgen::JsCast;
 
+use super::registry::{self, sanitize_id};
+
 struct DialogElements {
     border: Element,
     title_text: Element,
@@ -8,10 +10,10 @@ struct DialogElements {
     frame: Element,
 }
 
-struct ResizeOptions {
-    id: Option<String>,
-    width: Option<i32>,
-    height: Option<i32>,
+pub struct ResizeOptions {
+    pub id: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
 }
 
 pub fn resize_modal_dialog(options: ResizeOptions) {
@@ -34,12 +36,10 @@ fn find_modal_dialog(id: Option<String>) -> Option<Element> {
     let window = window().unwrap();
     let document = window.document().unwrap();
     
-    if let Some(id) = id {
-        document.get_element_by_id(&format!("sp_frame_{}", id))
-    } else {
-        // Assuming we have a way to get the last modal dialog ID
-        // This part would need to be implemented based on how you're tracking modal dialogs
-        None
+    match id {
+        Some(id) => document.get_element_by_id(&format!("sp_frame_{}", sanitize_id(&id))),
+        // without id, resize the topmost dialog
+        None => document.get_element_by_id(&registry::last()?.frame_id()),
     }
 }
 
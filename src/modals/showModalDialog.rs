@@ -1,8 +1,20 @@
-use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
-use wasm_bindgen_futures::spawn_local;
-use web_sys::{window, Document, Element, HtmlElement};
-use js_sys::Promise;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{window, Document, Element};
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::registry::{self, DialogOptions};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["SP", "SOD"], js_name = executeFunc)]
+    fn execute_func(script_name: &str, type_name: &str, f: &JsValue);
+}
+
+// Space kept between the dialog and the borders of the window
+const WINDOW_MARGIN: i32 = 20;
 
 #[wasm_bindgen]
 pub struct ModalDialog {
@@ -10,7 +22,9 @@ pub struct ModalDialog {
     options: ModalOptions,
 }
 
-#[derive(Default)]
+/// Options given from JavaScript, e.g. `{ title: "Hello", html: "<p>World</p>", closePrevious: true }`
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct ModalOptions {
     id: Option<String>,
     title: Option<String>,
@@ -23,22 +37,63 @@ pub struct ModalOptions {
     show_close: bool,
     hide_close: bool,
     url: Option<String>,
-    on_load: Option<js_sys::Function>,
-    on_url_load: Option<js_sys::Function>,
+    #[serde(rename = "onload", serialize_with = "serialize_function", deserialize_with = "deserialize_function")]
+    on_load: Option<Function>,
+    #[serde(rename = "onurlload", serialize_with = "serialize_function", deserialize_with = "deserialize_function")]
+    on_url_load: Option<Function>,
+}
+
+// The missing fields of the JS options get these values too
+impl Default for ModalOptions {
+    fn default() -> Self {
+        ModalOptions {
+            id: None,
+            title: None,
+            message: None,
+            html: None,
+            width: None,
+            height: None,
+            wait: false,
+            close_previous: false,
+            show_close: true,
+            hide_close: false,
+            url: None,
+            on_load: None,
+            on_url_load: None,
+        }
+    }
+}
+
+// JS functions can't go through serde, keep them as they are (anything else is ignored)
+fn serialize_function<S: Serializer>(f: &Option<Function>, serializer: S) -> Result<S::Ok, S::Error> {
+    match f {
+        Some(f) => serde_wasm_bindgen::preserve::serialize(f, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_function<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Function>, D::Error> {
+    let value: JsValue = serde_wasm_bindgen::preserve::deserialize(deserializer)?;
+    Ok(value.dyn_into::<Function>().ok())
 }
 
 #[wasm_bindgen]
 impl ModalDialog {
     #[wasm_bindgen(constructor)]
     pub fn new(options: JsValue) -> Result<ModalDialog, JsValue> {
-        let options: ModalOptions = options.into_serde().map_err(|_| "Invalid options")?;
-        let id = options.id.clone().unwrap_or_else(|| js_sys::Date::now().to_string());
+        let options: ModalOptions = serde_wasm_bindgen::from_value(options)
+            .map_err(|e| format!("[SharepointSharp 'showModalDialog'] invalid options: {}", e))?;
+        let id = registry::sanitize_id(options.id.as_deref().unwrap_or_default());
+        let id = if id.is_empty() { js_sys::Date::now().to_string() } else { id };
         Ok(ModalDialog { id, options })
     }
 
+    /// Show the dialog
+    ///
+    /// The promise resolves with `{ dialogResult, returnValue }` once the dialog is closed
     pub fn show(&self) -> Promise {
         let id = self.id.clone();
-        let options = self.options.clone();
+        let mut options = self.options.clone();
 
         future_to_promise(async move {
             if !is_sp_modal_dialog_loaded() {
@@ -48,9 +103,10 @@ impl ModalDialog {
             let modal_id = format!("sp_frame_{}", id);
             let document = window().unwrap().document().unwrap();
 
-            if let Some(html) = &options.html {
-                create_html_content(&document, &modal_id, html);
-            }
+            let html = options
+                .html
+                .as_deref()
+                .map(|html| create_html_content(&document, &modal_id, html));
 
             adjust_size(&mut options);
 
@@ -58,80 +114,196 @@ impl ModalDialog {
                 close_previous_dialog();
             }
 
-            let modal = show_modal(&options);
-            setup_modal_frame(&document, &modal_id, &options);
+            // resolved by SharePoint (or close_modal_dialog for "wait") when the dialog closes
+            let mut resolve_result = None;
+            let result = Promise::new(&mut |resolve, _reject| resolve_result = Some(resolve));
+            let resolve_result = resolve_result.unwrap();
+            let closed_id = id.clone();
+            let callback = Closure::once_into_js(move |dialog_result: JsValue, return_value: JsValue| {
+                // also closed from its own close button, not only through close_modal_dialog
+                registry::unregister(&closed_id);
+                let result = Object::new();
+                let _ = Reflect::set(&result, &"dialogResult".into(), &dialog_result);
+                let _ = Reflect::set(&result, &"returnValue".into(), &return_value);
+                let _ = resolve_result.call1(&JsValue::NULL, &result);
+            });
+            let callback: Function = callback.unchecked_into();
 
-            if let Some(on_load) = options.on_load {
-                on_load.call0(&JsValue::NULL)?;
+            let modal = show_modal(&options, html.as_ref(), &callback)?;
+            registry::register(registry::OpenDialog {
+                id: id.clone(),
+                modal: modal.clone().unchecked_into(),
+                options: DialogOptions {
+                    wait: options.wait,
+                    dialog_return_value_callback: Some(callback),
+                },
+            });
+            setup_modal_frame(&modal, &modal_id)?;
+
+            if let Some(on_load) = &options.on_load {
+                on_load.call1(&JsValue::NULL, &modal)?;
             }
 
-            if let Some(url) = &options.url {
-                if let Some(on_url_load) = options.on_url_load {
-                    setup_iframe_ready(&document, &modal_id, url, on_url_load);
+            if options.url.is_some() {
+                if let Some(on_url_load) = &options.on_url_load {
+                    setup_iframe_ready(&document, &modal_id, on_url_load);
                 }
             }
 
-            Ok(JsValue::NULL)
+            JsFuture::from(result).await
         })
     }
 }
 
+// SP.UI.ModalDialog, or an error when sp.ui.dialog.js isn't loaded
+fn sp_modal_dialog() -> Result<JsValue, JsValue> {
+    let sp = Reflect::get(&window().unwrap(), &"SP".into())?;
+    let ui = Reflect::get(&sp, &"UI".into())?;
+    Reflect::get(&ui, &"ModalDialog".into())
+}
+
 fn is_sp_modal_dialog_loaded() -> bool {
-    // This would need to be implemented to check if SP.UI.ModalDialog is available
-    false
+    sp_modal_dialog().is_ok_and(|modal_dialog| !modal_dialog.is_undefined())
 }
 
 async fn load_sp_ui_dialog_js() -> Result<(), JsValue> {
-    // This would need to be implemented to load the SP UI Dialog JS file
+    let loaded = Promise::new(&mut |resolve, _reject| {
+        execute_func("sp.ui.dialog.js", "SP.UI.ModalDialog.showModalDialog", &resolve);
+    });
+    JsFuture::from(loaded).await?;
     Ok(())
 }
 
-fn create_html_content(document: &Document, modal_id: &str, html: &str) {
+fn create_html_content(document: &Document, modal_id: &str, html: &str) -> Element {
     let div = document.create_element("div").unwrap();
     div.set_attribute("style", "padding:10px;display:inline-block").unwrap();
     div.set_class_name("sp-showModalDialog");
     div.set_id(&format!("content_{}", modal_id));
     div.set_inner_html(html);
-    // You would need to add this div to the document or the modal
+    div
 }
 
+// Make sure the dialog fits in the window; a size that isn't a number of pixels
+// is dropped so SharePoint sizes the dialog itself
 fn adjust_size(options: &mut ModalOptions) {
-    // This function would adjust the width and height based on the viewport size
-    // Similar to the JavaScript version, but using Rust's logic
+    let window = window().unwrap();
+    let window_width = window.inner_width().ok().and_then(|w| w.as_f64());
+    let window_height = window.inner_height().ok().and_then(|h| h.as_f64());
+
+    options.width = clamp_size(options.width.as_deref(), window_width);
+    options.height = clamp_size(options.height.as_deref(), window_height);
+}
+
+// A size in pixels, reduced to fit in the window when its size is known
+fn clamp_size(size: Option<&str>, window_size: Option<f64>) -> Option<String> {
+    let size = size?.trim().trim_end_matches("px").parse::<i32>().ok()?;
+    let max = window_size
+        .map(|window_size| window_size as i32 - 2 * WINDOW_MARGIN)
+        .filter(|max| *max > 0);
+    Some(match max {
+        Some(max) => size.min(max).max(0),
+        None => size.max(0),
+    }
+    .to_string())
 }
 
 fn close_previous_dialog() {
-    // This function would close the previous dialog
-    // You'd need to implement this based on how you're tracking dialogs
+    if let Some(dialog) = registry::last() {
+        dialog.close(JsValue::UNDEFINED);
+    }
 }
 
-fn show_modal(options: &ModalOptions) -> JsValue {
-    // This function would show the modal dialog
-    // You'd need to call into JavaScript to use SP.UI.ModalDialog.showModalDialog
-    JsValue::NULL
+// Call SP.UI.ModalDialog.showModalDialog (or showWaitScreenWithNoClose) and
+// return the SP.UI.ModalDialog instance
+fn show_modal(options: &ModalOptions, html: Option<&Element>, callback: &Function) -> Result<JsValue, JsValue> {
+    let modal_dialog = sp_modal_dialog()?;
+    let size = |size: &Option<String>| match size.as_deref().and_then(|s| s.parse::<f64>().ok()) {
+        Some(size) => JsValue::from_f64(size),
+        None => JsValue::UNDEFINED,
+    };
+
+    if options.wait {
+        let show: Function = Reflect::get(&modal_dialog, &"showWaitScreenWithNoClose".into())?.dyn_into()?;
+        let args = Array::of4(
+            &options.title.as_deref().unwrap_or_default().into(),
+            &options.message.as_deref().unwrap_or_default().into(),
+            &size(&options.height),
+            &size(&options.width),
+        );
+        return show.apply(&modal_dialog, &args);
+    }
+
+    let sp_options = Object::new();
+    if let Some(title) = &options.title {
+        Reflect::set(&sp_options, &"title".into(), &title.into())?;
+    }
+    if let Some(url) = &options.url {
+        Reflect::set(&sp_options, &"url".into(), &url.into())?;
+    }
+    if let Some(html) = html {
+        Reflect::set(&sp_options, &"html".into(), html)?;
+    }
+    let auto_size = options.width.is_none() || options.height.is_none();
+    Reflect::set(&sp_options, &"autoSize".into(), &auto_size.into())?;
+    Reflect::set(&sp_options, &"width".into(), &size(&options.width))?;
+    Reflect::set(&sp_options, &"height".into(), &size(&options.height))?;
+    Reflect::set(&sp_options, &"showClose".into(), &(options.show_close && !options.hide_close).into())?;
+    Reflect::set(&sp_options, &"dialogReturnValueCallback".into(), callback)?;
+
+    let show: Function = Reflect::get(&modal_dialog, &"showModalDialog".into())?.dyn_into()?;
+    show.call1(&modal_dialog, &sp_options)
 }
 
-fn setup_modal_frame(document: &Document, modal_id: &str, options: &ModalOptions) {
-    // This function would set up the modal frame, including z-index handling
-    // You'd need to implement this based on your specific requirements
+// Give the dialog element the id used by get/resize_modal_dialog
+fn setup_modal_frame(modal: &JsValue, modal_id: &str) -> Result<(), JsValue> {
+    let get_dialog_element: Function = Reflect::get(modal, &"get_dialogElement".into())?.dyn_into()?;
+    let dialog_element: Element = get_dialog_element.call0(modal)?.dyn_into()?;
+    dialog_element.set_id(modal_id);
+    Ok(())
 }
 
-fn setup_iframe_ready(document: &Document, modal_id: &str, url: &str, on_url_load: js_sys::Function) {
-    // This function would set up the iframe ready event
-    // You'd need to implement this based on your specific requirements
+// Call on_url_load each time the iframe of the dialog has loaded its page
+fn setup_iframe_ready(document: &Document, modal_id: &str, on_url_load: &Function) {
+    let iframe = document
+        .get_element_by_id(modal_id)
+        .and_then(|dlg| dlg.query_selector("iframe").ok().flatten());
+    if let Some(iframe) = iframe {
+        let _ = iframe.add_event_listener_with_callback("load", on_url_load);
+    }
 }
 
-fn future_to_promise<F>(future: F) -> Promise
-where
-    F: Future<Output = Result<JsValue, JsValue>> + 'static,
-{
-    let promise = js_sys::Promise::new(&mut |resolve, reject| {
-        spawn_local(async move {
-            match future.await {
-                Ok(value) => resolve.call1(&JsValue::NULL, &value).unwrap(),
-                Err(error) => reject.call1(&JsValue::NULL, &error).unwrap(),
-            }
-        });
-    });
-    promise
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn show_close_by_default() {
+        let options: ModalOptions = serde_json::from_str(r#"{"title":"Hello","html":"<p>World</p>"}"#).unwrap();
+        assert!(options.show_close);
+        assert!(!options.hide_close);
+
+        let options: ModalOptions = serde_json::from_str(r#"{"title":"Hello","showClose":false}"#).unwrap();
+        assert!(!options.show_close);
+
+        let options = ModalOptions::default();
+        assert!(options.show_close);
+        assert!(!options.hide_close);
+    }
+
+    #[test]
+    fn clamp_to_the_window() {
+        assert_eq!(clamp_size(Some("500"), Some(1024.0)), Some("500".to_string()));
+        assert_eq!(clamp_size(Some("2000px"), Some(1024.0)), Some("984".to_string()));
+        assert_eq!(clamp_size(Some("-5"), Some(1024.0)), Some("0".to_string()));
+        // not a number of pixels
+        assert_eq!(clamp_size(Some("50%"), Some(1024.0)), None);
+        assert_eq!(clamp_size(None, Some(1024.0)), None);
+    }
+
+    #[test]
+    fn no_clamp_without_window_size() {
+        assert_eq!(clamp_size(Some("2000"), None), Some("2000".to_string()));
+        assert_eq!(clamp_size(Some("2000"), Some(0.0)), Some("2000".to_string()));
+        assert_eq!(clamp_size(Some("2000"), Some(30.0)), Some("2000".to_string()));
+    }
+}
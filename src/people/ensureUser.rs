@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use reqwest::Client;
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::utils::getFormDigest::get_form_digest;
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
+
+/// A site user, as needed to set a person field
+#[derive(Debug, Clone)]
+pub struct SpUser {
+    pub id: u32,
+    pub title: String,
+    pub login_name: String,
+    pub email: String,
+}
+
+/// Resolve a login into a site user, adding the user to the site if needed
+pub async fn ensure_user(login: &str, setup: Option<HashMap<String, String>>) -> Result<SpUser, Box<dyn std::error::Error + Send + Sync>> {
+    if login.is_empty() {
+        return Err("[SharepointSharp 'ensureUser'] the login is required.".into());
+    }
+
+    let setup = setup.unwrap_or_default();
+    let url = discover_url(setup.get("url").map(String::as_str))?;
+    let digest = get_form_digest(Some(&url)).await?;
+
+    let response = Client::new()
+        .post(resolve_url(&Url::parse(&url)?, "_api/web/ensureuser")?)
+        .header("Accept", "application/json;odata=verbose")
+        .header("Content-Type", "application/json;odata=verbose")
+        .header("X-RequestDigest", digest.value)
+        .body(json!({ "logonName": login }).to_string())
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("[SharepointSharp 'ensureUser'] unable to resolve '{}': {}", login, response.status()).into());
+    }
+
+    let data: Value = serde_json::from_str(&response.text().await?)?;
+    let user = &data["d"];
+    let id = user["Id"]
+        .as_u64()
+        .ok_or("[SharepointSharp 'ensureUser'] no Id in the response")?;
+    let field = |name: &str| user[name].as_str().unwrap_or_default().to_string();
+
+    Ok(SpUser {
+        id: id as u32,
+        title: field("Title"),
+        login_name: field("LoginName"),
+        email: field("Email"),
+    })
+}
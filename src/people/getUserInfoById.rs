@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use url::Url;
+
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
+
+/// A site user, with the same information as the UserGroup.asmx `GetUserInfo` result
+/// (`Notes` and `Flags` have no REST equivalent)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserInfo {
+    pub id: u32,
+    pub sid: String,
+    pub name: String,
+    pub login_name: String,
+    pub email: String,
+    pub is_site_admin: bool,
+    pub is_domain_group: bool,
+}
+
+#[derive(Debug)]
+pub enum UserInfoError {
+    /// No site user has this ID
+    UserNotFound(u32),
+    Request(String),
+}
+
+impl std::fmt::Display for UserInfoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserInfoError::UserNotFound(id) => write!(f, "[SharepointSharp 'getUserInfo'] no user with the ID {}", id),
+            UserInfoError::Request(msg) => write!(f, "[SharepointSharp 'getUserInfo'] {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for UserInfoError {}
+
+/// Get the info of a site user from its ID, e.g. the `5` of `Author=5;#John`
+pub async fn get_user_info_by_id(id: u32, setup: Option<HashMap<String, String>>) -> Result<UserInfo, UserInfoError> {
+    let setup = setup.unwrap_or_default();
+    let url = discover_url(setup.get("url").map(String::as_str)).map_err(|err| UserInfoError::Request(err.to_string()))?;
+    let url = Url::parse(&url)
+        .and_then(|site| resolve_url(&site, &format!("_api/web/getuserbyid({})", id)))
+        .map_err(|err| UserInfoError::Request(format!("invalid URL: {}", err)))?;
+
+    let response = Client::new()
+        .get(url)
+        .header("Accept", "application/json;odata=verbose")
+        .send()
+        .await
+        .map_err(|err| UserInfoError::Request(format!("Error sending request: {}", err)))?;
+    check_status(id, response.status())?;
+
+    let text = response
+        .text()
+        .await
+        .map_err(|err| UserInfoError::Request(format!("Error reading response: {}", err)))?;
+    let data: Value = serde_json::from_str(&text).map_err(|err| UserInfoError::Request(format!("invalid response: {}", err)))?;
+    user_info_from_json(&data["d"])
+}
+
+// SharePoint answers 404 when the ID isn't a site user
+fn check_status(id: u32, status: StatusCode) -> Result<(), UserInfoError> {
+    if status == StatusCode::NOT_FOUND {
+        return Err(UserInfoError::UserNotFound(id));
+    }
+    if !status.is_success() {
+        return Err(UserInfoError::Request(format!("the request returned {}", status)));
+    }
+    Ok(())
+}
+
+// Map the `d` object of `_api/web/getuserbyid`
+fn user_info_from_json(user: &Value) -> Result<UserInfo, UserInfoError> {
+    let id = user["Id"]
+        .as_u64()
+        .ok_or_else(|| UserInfoError::Request("no Id in the response".to_string()))?;
+    let field = |name: &str| user[name].as_str().unwrap_or_default().to_string();
+
+    Ok(UserInfo {
+        id: id as u32,
+        sid: user["UserId"]["NameId"].as_str().unwrap_or_default().to_string(),
+        name: field("Title"),
+        login_name: field("LoginName"),
+        email: field("Email"),
+        is_site_admin: user["IsSiteAdmin"].as_bool().unwrap_or(false),
+        // PrincipalType 4 = SecurityGroup
+        is_domain_group: user["PrincipalType"].as_u64() == Some(4),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn maps_the_rest_user() {
+        let user = json!({
+            "Id": 5,
+            "Title": "John Doe",
+            "LoginName": "i:0#.w|domain\\john",
+            "Email": "john@example.com",
+            "IsSiteAdmin": true,
+            "PrincipalType": 1,
+            "UserId": { "NameId": "s-1-5-21", "NameIdIssuer": "urn:office:idp:activedirectory" }
+        });
+        assert_eq!(
+            user_info_from_json(&user).unwrap(),
+            UserInfo {
+                id: 5,
+                sid: "s-1-5-21".to_string(),
+                name: "John Doe".to_string(),
+                login_name: "i:0#.w|domain\\john".to_string(),
+                email: "john@example.com".to_string(),
+                is_site_admin: true,
+                is_domain_group: false,
+            }
+        );
+    }
+
+    #[test]
+    fn maps_a_security_group_without_user_id() {
+        let group = json!({ "Id": 7, "Title": "Readers", "UserId": null, "PrincipalType": 4 });
+        let info = user_info_from_json(&group).unwrap();
+        assert_eq!(info.id, 7);
+        assert_eq!(info.sid, "");
+        assert_eq!(info.email, "");
+        assert!(info.is_domain_group);
+        assert!(!info.is_site_admin);
+    }
+
+    #[test]
+    fn missing_id_is_an_error() {
+        assert!(matches!(user_info_from_json(&json!({ "Title": "x" })), Err(UserInfoError::Request(_))));
+    }
+
+    #[test]
+    fn not_found_is_user_not_found() {
+        assert!(matches!(check_status(5, StatusCode::NOT_FOUND), Err(UserInfoError::UserNotFound(5))));
+        assert_eq!(
+            check_status(5, StatusCode::NOT_FOUND).unwrap_err().to_string(),
+            "[SharepointSharp 'getUserInfo'] no user with the ID 5"
+        );
+    }
+
+    #[test]
+    fn other_statuses() {
+        assert!(check_status(5, StatusCode::OK).is_ok());
+        assert!(matches!(check_status(5, StatusCode::FORBIDDEN), Err(UserInfoError::Request(_))));
+    }
+}
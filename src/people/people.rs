@@ -1,69 +1,88 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::stream::{self, StreamExt};
+use quick_xml::events::Event;
+use quick_xml::Reader;
 use reqwest::Client;
-use xml::reader::{Parser, EventReader};
-use xml::ElementReader;
+use url::Url;
+
+use crate::utils::buildBodyForSOAP::build_body_for_soap;
+use crate::utils::escapeXML::escape_xml;
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
 
-#[derive(Debug)]
+// Number of GetUserProfileByName requests sent at the same time by people_many
+const PEOPLE_CONCURRENCY: usize = 5;
+// How long people_many keeps a profile in cache
+const PEOPLE_CACHE_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
 pub struct UserProfile {
     pub name: String,
     pub value: String,
 }
 
-pub async fn people(username: Option<String>, setup: Option<HashMap<String, String>>) -> Result<Vec<UserProfile>, reqwest::Error> {
+pub async fn people(username: Option<String>, setup: Option<HashMap<String, String>>) -> Result<Vec<UserProfile>, Box<dyn std::error::Error + Send + Sync>> {
     let username = username.unwrap_or_default();
     let mut setup = setup.unwrap_or_default();
 
-    if !setup.contains_key("url") {
-        let url = get_url().await?;
-        setup.insert("url".to_string(), url);
-    }
+    let url = discover_url(setup.get("url").map(String::as_str))?;
+    setup.insert("url".to_string(), url);
 
     let client = Client::new();
-    let url = format!("{}/_vti_bin/UserProfileService.asmx", setup["url"].clone());
+    let url = resolve_url(&Url::parse(&setup["url"])?, "_vti_bin/UserProfileService.asmx")?;
 
-    let soap_body = build_body_for_soap("GetUserProfileByName", &format!("<AccountName>{}</AccountName>", username), "http://microsoft.com/webservices/SharePointPortalServer/UserProfileService");
+    let soap_body = build_body_for_soap("GetUserProfileByName", &format!("<AccountName>{}</AccountName>", escape_xml(&username)), Some("http://microsoft.com/webservices/SharePointPortalServer/UserProfileService"));
 
-    let response = client.post(&url)
+    let response = client.post(url)
+        .header("Content-Type", "text/xml; charset=utf-8")
         .header("SOAPAction", "http://microsoft.com/webservices/SharePointPortalServer/UserProfileService/GetUserProfileByName")
         .body(soap_body)
         .send()
         .await?;
 
+    let response_xml = response.text().await?;
+    Ok(parse_profile(&response_xml)?)
+}
+
+// Read the <Name> and <Value> of each <PropertyData> of the GetUserProfileByName response
+fn parse_profile(xml: &str) -> Result<Vec<UserProfile>, quick_xml::Error> {
+    let mut reader = Reader::from_str(xml);
     let mut result = Vec::new();
-    let mut parser = Parser::new(response.text().await?);
-    let mut reader = EventReader::from(parser);
 
     let mut current_tag: Option<String> = None;
     let mut current_name: Option<String> = None;
     let mut current_value: Option<String> = None;
 
-    while let Some(e) = reader.next() {
-        match e {
-            Ok(reader::Event::StartElement { name, attributes }) => {
-                current_tag = Some(name.local_name().to_string());
-                if current_tag == Some("Name".to_string()) {
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                current_tag = Some(String::from_utf8_lossy(e.local_name().as_ref()).to_string());
+                if current_tag.as_deref() == Some("Name") {
                     current_name = None;
-                } else if current_tag == Some("Value".to_string()) {
+                } else if current_tag.as_deref() == Some("Value") {
                     current_value = None;
                 }
-            },
-            Ok(reader::Event::Characters(text)) => {
-                if current_tag == Some("Name".to_string()) {
-                    current_name = Some(text.to_string());
-                } else if current_tag == Some("Value".to_string()) {
-                    current_value = Some(text.to_string());
+            }
+            Event::Text(e) => {
+                let text = e.unescape()?.to_string();
+                if current_tag.as_deref() == Some("Name") {
+                    current_name = Some(text);
+                } else if current_tag.as_deref() == Some("Value") {
+                    current_value = Some(text);
                 }
-            },
-            Ok(reader::Event::EndElement { name }) => {
-                if name.local_name().to_string() == "PropertyData" {
-                    if let (Some(name), Some(value)) = (current_name, current_value) {
+            }
+            Event::End(e) => {
+                if e.local_name().as_ref() == b"PropertyData" {
+                    if let (Some(name), Some(value)) = (current_name.take(), current_value.take()) {
                         result.push(UserProfile { name, value });
                     }
-                    current_name = None;
-                    current_value = None;
                 }
                 current_tag = None;
-            },
-            Err(e) => return Err(reqwest::Error::new(e.to_string())),
+            }
+            Event::Eof => break,
             _ => {}
         }
     }
@@ -71,21 +90,183 @@ pub async fn people(username: Option<String>, setup: Option<HashMap<String, Stri
     Ok(result)
 }
 
-fn build_body_for_soap(method: &str, body: &str, namespace: &str) -> String {
-    format!(
-        r#"<?xml version="1.0" encoding="utf-8"?>
-<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+/// URL of the profile picture of a user (`PictureURL` profile property), or `None` if unset
+pub async fn people_photo_url(username: Option<String>, setup: Option<HashMap<String, String>>) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let profile = people(username, setup).await?;
+    let url = profile
+        .into_iter()
+        .find(|property| property.name == "PictureURL")
+        .map(|property| property.value.trim().to_string())
+        .filter(|url| !url.is_empty());
+    Ok(url)
+}
+
+struct PeopleCacheEntry {
+    user: String,
+    url: String,
+    data: Vec<UserProfile>,
+    time: Instant,
+}
+
+/// Profile of one user in the `people_many` result
+pub type PeopleResult = Result<Vec<UserProfile>, Box<dyn std::error::Error + Send + Sync>>;
+
+static SP_CACHE_PEOPLE: LazyLock<Mutex<Vec<PeopleCacheEntry>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+// The cached profile of a user, dropping the expired entries on the way
+fn cached_profile(user: &str, url: &str) -> Option<Vec<UserProfile>> {
+    let mut people_cache = SP_CACHE_PEOPLE.lock().unwrap();
+    people_cache.retain(|c| c.time.elapsed() < PEOPLE_CACHE_TTL);
+    people_cache.iter().find(|c| c.user == user && c.url == url).map(|c| c.data.clone())
+}
+
+fn cache_profile(user: &str, url: &str, data: &[UserProfile]) {
+    let mut people_cache = SP_CACHE_PEOPLE.lock().unwrap();
+    match people_cache.iter_mut().find(|c| c.user == user && c.url == url) {
+        Some(c) => {
+            c.data = data.to_vec();
+            c.time = Instant::now();
+        }
+        None => people_cache.push(PeopleCacheEntry {
+            user: user.to_string(),
+            url: url.to_string(),
+            data: data.to_vec(),
+            time: Instant::now(),
+        }),
+    }
+}
+
+/// Get the profiles of several users, with at most 5 requests at the same time
+///
+/// The result is keyed by lowercase username, with the profile or the error of
+/// each user: a failing user doesn't discard the others. Profiles are cached for
+/// 5 minutes per username and URL, unless `setup` has `cache` set to `"false"`.
+pub async fn people_many(usernames: Vec<String>, setup: Option<HashMap<String, String>>) -> Result<HashMap<String, PeopleResult>, Box<dyn std::error::Error + Send + Sync>> {
+    let setup = setup.unwrap_or_default();
+    let cache = setup.get("cache").is_none_or(|val| val != "false");
+    let url = discover_url(setup.get("url").map(String::as_str))?.to_lowercase();
+
+    let mut result = HashMap::new();
+    let mut missing: Vec<String> = Vec::new();
+
+    // Check the cache
+    for username in usernames {
+        let username = username.to_lowercase();
+        if result.contains_key(&username) || missing.contains(&username) {
+            continue;
+        }
+        if cache {
+            if let Some(data) = cached_profile(&username, &url) {
+                result.insert(username, Ok(data));
+                continue;
+            }
+        }
+        missing.push(username);
+    }
+
+    // Send the requests
+    let fetched: Vec<_> = stream::iter(missing)
+        .map(|username| {
+            let setup = setup.clone();
+            async move {
+                let data = people(Some(username.clone()), Some(setup)).await;
+                (username, data)
+            }
+        })
+        .buffer_unordered(PEOPLE_CONCURRENCY)
+        .collect()
+        .await;
+
+    for (username, data) in fetched {
+        let data = match data {
+            Ok(data) => data,
+            Err(e) => {
+                result.insert(username, Err(e));
+                continue;
+            }
+        };
+
+        cache_profile(&username, &url, &data);
+        result.insert(username, Ok(data));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_profile_properties() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
   <soap:Body>
-    <{} xmlns="{}">
-      {}
-    </{}>
+    <GetUserProfileByNameResponse xmlns="http://microsoft.com/webservices/SharePointPortalServer/UserProfileService">
+      <GetUserProfileByNameResult>
+        <PropertyData><IsPrivacyChanged>false</IsPrivacyChanged><Name>AccountName</Name><Values><ValueData><Value xsi:type="xsd:string">contoso\john</Value></ValueData></Values></PropertyData>
+        <PropertyData><Name>PreferredName</Name><Values><ValueData><Value xsi:type="xsd:string">John &amp; Co</Value></ValueData></Values></PropertyData>
+        <PropertyData><Name>PictureURL</Name><Values /></PropertyData>
+      </GetUserProfileByNameResult>
+    </GetUserProfileByNameResponse>
   </soap:Body>
-</soap:Envelope>"#,
-        method, namespace, body, method
-    )
-}
+</soap:Envelope>"#;
+        let profile = parse_profile(xml).unwrap();
+        let properties: Vec<(&str, &str)> = profile.iter().map(|p| (p.name.as_str(), p.value.as_str())).collect();
+        assert_eq!(properties, [("AccountName", "contoso\\john"), ("PreferredName", "John & Co")]);
+    }
+
+    #[test]
+    fn people_many_can_be_spawned() {
+        fn assert_send<T: Send>(_: &T) {}
+        assert_send(&people_many(vec!["john".to_string()], None));
+    }
 
-async fn get_url() -> Result<String, reqwest::Error> {
-    // Implement your logic to get the URL here (replace with actual implementation)
-    todo!("Implement get_url function to retrieve the URL")
+    fn profile(name: &str) -> Vec<UserProfile> {
+        vec![UserProfile {
+            name: "AccountName".to_string(),
+            value: name.to_string(),
+        }]
+    }
+
+    fn entries(url: &str) -> usize {
+        SP_CACHE_PEOPLE.lock().unwrap().iter().filter(|c| c.url == url).count()
+    }
+
+    #[test]
+    fn cached_profiles_are_found_whatever_the_case() {
+        let url = "https://contoso.sharepoint.com/sites/people-hit";
+        cache_profile("john", url, &profile("contoso\\john"));
+
+        // served from the cache without a request, once for both spellings
+        let setup = HashMap::from([("url".to_string(), "https://Contoso.sharepoint.com/sites/People-Hit".to_string())]);
+        let result = futures::executor::block_on(people_many(vec!["John".to_string(), "JOHN".to_string()], Some(setup))).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result["john"].as_ref().unwrap()[0].value, "contoso\\john");
+    }
+
+    #[test]
+    fn expired_profiles_are_evicted() {
+        let url = "https://contoso.sharepoint.com/sites/people-expired";
+        SP_CACHE_PEOPLE.lock().unwrap().push(PeopleCacheEntry {
+            user: "john".to_string(),
+            url: url.to_string(),
+            data: profile("contoso\\john"),
+            time: Instant::now().checked_sub(PEOPLE_CACHE_TTL).unwrap(),
+        });
+
+        assert!(cached_profile("john", url).is_none());
+        assert_eq!(entries(url), 0);
+    }
+
+    #[test]
+    fn a_profile_is_cached_once() {
+        let url = "https://contoso.sharepoint.com/sites/people-dedup";
+        cache_profile("john", url, &profile("old"));
+        cache_profile("john", url, &profile("new"));
+        cache_profile("jane", url, &profile("jane"));
+
+        assert_eq!(entries(url), 2);
+        assert_eq!(cached_profile("john", url).unwrap()[0].value, "new");
+    }
 }
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use reqwest::Client;
+use url::Url;
+
+use crate::utils::buildBodyForSOAP::build_body_for_soap;
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
+use crate::utils::xmlElements::parse_elements;
+
+/// A subsite, from a `<Web>` of `GetWebCollection`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSummary {
+    pub title: String,
+    pub url: String,
+}
+
+/// Get the direct subsites of a site with Webs.asmx `GetWebCollection`
+pub async fn get_subwebs(setup: Option<HashMap<String, String>>) -> Result<Vec<WebSummary>, Box<dyn std::error::Error + Send + Sync>> {
+    let setup = setup.unwrap_or_default();
+    let site = Url::parse(&discover_url(setup.get("url").map(String::as_str))?)?;
+
+    let response = Client::new()
+        .post(resolve_url(&site, "_vti_bin/Webs.asmx")?)
+        .header("Content-Type", "text/xml; charset=utf-8")
+        .header("SOAPAction", "http://schemas.microsoft.com/sharepoint/soap/GetWebCollection")
+        .body(build_body_for_soap("GetWebCollection", "", None))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("[SharepointSharp 'getSubwebs'] unable to get the subsites: {}", response.status()).into());
+    }
+
+    Ok(parse_webs(&response.text().await?)?)
+}
+
+fn parse_webs(xml: &str) -> Result<Vec<WebSummary>, quick_xml::Error> {
+    parse_elements(xml, "Web", |attrs| {
+        Ok(WebSummary {
+            title: attrs.get("Title")?,
+            url: attrs.get("Url")?,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webs() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <GetWebCollectionResponse xmlns="http://schemas.microsoft.com/sharepoint/soap/">
+      <GetWebCollectionResult>
+        <Webs>
+          <Web Title="Projects" Url="https://contoso.sharepoint.com/sites/x/projects" />
+          <Web Title="R&amp;D" Url="https://contoso.sharepoint.com/sites/x/rd" />
+        </Webs>
+      </GetWebCollectionResult>
+    </GetWebCollectionResponse>
+  </soap:Body>
+</soap:Envelope>"#;
+        assert_eq!(
+            parse_webs(xml).unwrap(),
+            [
+                WebSummary {
+                    title: "Projects".to_string(),
+                    url: "https://contoso.sharepoint.com/sites/x/projects".to_string(),
+                },
+                WebSummary {
+                    title: "R&D".to_string(),
+                    url: "https://contoso.sharepoint.com/sites/x/rd".to_string(),
+                },
+            ]
+        );
+        assert!(parse_webs("<Webs></Webs>").unwrap().is_empty());
+    }
+}
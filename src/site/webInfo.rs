@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use url::Url;
+
+use crate::utils::getURL::discover_url;
+use crate::utils::resolveURL::resolve_url;
+
+/// Metadata of a site, from `_api/web`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WebInfo {
+    pub title: String,
+    /// ISO 8601 date, e.g. `2020-01-31T08:00:00Z`
+    pub created: String,
+    /// LCID, e.g. 1033 for English
+    pub language: u32,
+    /// e.g. `STS` for a team site
+    pub web_template: String,
+    /// e.g. `/sites/x`
+    pub server_relative_url: String,
+}
+
+// Web info by site URL; it doesn't change while the program runs
+static SP_CACHE_WEBINFO: LazyLock<Mutex<HashMap<String, WebInfo>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Get the title, creation date, language, template and server-relative URL of a site
+///
+/// The result is cached per site URL.
+pub async fn web_info(setup: Option<HashMap<String, String>>) -> Result<WebInfo, Box<dyn std::error::Error + Send + Sync>> {
+    let setup = setup.unwrap_or_default();
+    let url = discover_url(setup.get("url").map(String::as_str))?;
+    let key = url.to_lowercase();
+
+    if let Some(info) = SP_CACHE_WEBINFO.lock().unwrap().get(&key) {
+        return Ok(info.clone());
+    }
+
+    let response = Client::new()
+        .get(resolve_url(&Url::parse(&url)?, "_api/web?$select=Title,Created,Language,WebTemplate,ServerRelativeUrl")?)
+        .header("Accept", "application/json;odata=verbose")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("[SharepointSharp 'webInfo'] unable to get the site info: {}", response.status()).into());
+    }
+
+    let info = parse_web_info(&response.text().await?)?;
+    SP_CACHE_WEBINFO.lock().unwrap().insert(key, info.clone());
+    Ok(info)
+}
+
+fn parse_web_info(json: &str) -> Result<WebInfo, serde_json::Error> {
+    let mut data: Value = serde_json::from_str(json)?;
+    serde_json::from_value(data["d"].take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> WebInfo {
+        WebInfo {
+            title: "Team Site".to_string(),
+            created: "2020-01-31T08:00:00Z".to_string(),
+            language: 1033,
+            web_template: "STS".to_string(),
+            server_relative_url: "/sites/x".to_string(),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let json = r#"{"d":{"__metadata":{"type":"SP.Web"},"Created":"2020-01-31T08:00:00Z","Language":1033,"ServerRelativeUrl":"/sites/x","Title":"Team Site","WebTemplate":"STS"}}"#;
+        assert_eq!(parse_web_info(json).unwrap(), info());
+        assert!(parse_web_info(r#"{"d":{"Title":"Team Site"}}"#).is_err());
+    }
+
+    #[test]
+    fn cached_per_site_url() {
+        SP_CACHE_WEBINFO
+            .lock()
+            .unwrap()
+            .insert("https://contoso.sharepoint.com/sites/x".to_string(), info());
+
+        // no request is sent, whatever the case of the URL
+        let setup = HashMap::from([("url".to_string(), "https://Contoso.sharepoint.com/sites/X".to_string())]);
+        assert_eq!(futures::executor::block_on(web_info(Some(setup))).unwrap(), info());
+    }
+}
@@ -0,0 +1,162 @@
+use super::escapeXML::escape_xml;
+use super::formatField::FieldValue;
+
+/// Value of the `OnError` attribute of `<Batch>`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnError {
+    /// Keep processing the next methods when one fails
+    #[default]
+    Continue,
+    /// Stop at the first failing method
+    Return,
+}
+
+enum Cmd {
+    New,
+    Update(u32),
+    Delete(u32),
+}
+
+/// Build the `<Batch>` XML sent to `UpdateListItems`
+///
+/// Each call adds a `<Method>` with its own ID (starting at 1), in call order.
+/// Field names and values are XML-escaped. Values are strings, or a `FieldValue`
+/// for person, lookup and multi-choice fields.
+///
+/// ```ignore
+/// let batch = BatchBuilder::new()
+///     .on_error(OnError::Return)
+///     .new_item([("Title", "Hello")])
+///     .update_item(5, [("Title", "World")])
+///     .update_item(6, [("AssignedTo", FieldValue::Person(users))])
+///     .delete_item(7)
+///     .build();
+/// ```
+#[derive(Default)]
+pub struct BatchBuilder {
+    on_error: OnError,
+    root_folder: Option<String>,
+    methods: Vec<(Cmd, Vec<(String, String)>)>,
+}
+
+impl BatchBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    /// Folder (server relative path) where new items are created
+    pub fn root_folder(mut self, path: &str) -> Self {
+        self.root_folder = Some(path.to_string());
+        self
+    }
+
+    pub fn new_item<I, K, V>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<FieldValue>,
+    {
+        self.methods.push((Cmd::New, collect_fields(fields)));
+        self
+    }
+
+    pub fn update_item<I, K, V>(mut self, id: u32, fields: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<FieldValue>,
+    {
+        self.methods.push((Cmd::Update(id), collect_fields(fields)));
+        self
+    }
+
+    pub fn delete_item(mut self, id: u32) -> Self {
+        self.methods.push((Cmd::Delete(id), Vec::new()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.methods.is_empty()
+    }
+
+    pub fn build(&self) -> String {
+        let on_error = match self.on_error {
+            OnError::Continue => "Continue",
+            OnError::Return => "Return",
+        };
+        let mut batch = format!("<Batch OnError=\"{}\"", on_error);
+        if let Some(root_folder) = &self.root_folder {
+            batch.push_str(&format!(" RootFolder=\"{}\"", escape_xml(root_folder)));
+        }
+        batch.push('>');
+
+        for (index, (cmd, fields)) in self.methods.iter().enumerate() {
+            let (cmd, id) = match cmd {
+                Cmd::New => ("New", "New".to_string()),
+                Cmd::Update(id) => ("Update", id.to_string()),
+                Cmd::Delete(id) => ("Delete", id.to_string()),
+            };
+            batch.push_str(&format!("<Method ID=\"{}\" Cmd=\"{}\">", index + 1, cmd));
+            batch.push_str(&format!("<Field Name=\"ID\">{}</Field>", id));
+            for (name, value) in fields {
+                batch.push_str(&format!(
+                    "<Field Name=\"{}\">{}</Field>",
+                    escape_xml(name),
+                    escape_xml(value)
+                ));
+            }
+            batch.push_str("</Method>");
+        }
+
+        batch.push_str("</Batch>");
+        batch
+    }
+}
+
+fn collect_fields<I, K, V>(fields: I) -> Vec<(String, String)>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<FieldValue>,
+{
+    fields
+        .into_iter()
+        .map(|(name, value)| (name.into(), value.into().to_field_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_batch() {
+        let batch = BatchBuilder::new()
+            .on_error(OnError::Return)
+            .root_folder("/sites/x/Lists/Tasks/Folder")
+            .new_item([("Title", "Hello & Welcome")])
+            .update_item(5, [("Title", "World")])
+            .delete_item(7)
+            .build();
+        assert_eq!(
+            batch,
+            "<Batch OnError=\"Return\" RootFolder=\"/sites/x/Lists/Tasks/Folder\">\
+             <Method ID=\"1\" Cmd=\"New\"><Field Name=\"ID\">New</Field><Field Name=\"Title\">Hello &amp; Welcome</Field></Method>\
+             <Method ID=\"2\" Cmd=\"Update\"><Field Name=\"ID\">5</Field><Field Name=\"Title\">World</Field></Method>\
+             <Method ID=\"3\" Cmd=\"Delete\"><Field Name=\"ID\">7</Field></Method>\
+             </Batch>"
+        );
+    }
+
+    #[test]
+    fn empty_batch() {
+        let batch = BatchBuilder::new();
+        assert!(batch.is_empty());
+        assert_eq!(batch.build(), "<Batch OnError=\"Continue\"></Batch>");
+    }
+}
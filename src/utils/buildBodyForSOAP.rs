@@ -0,0 +1,19 @@
+/// Build the SOAP envelope for a SharePoint web service call
+///
+/// `namespace` defaults to `http://schemas.microsoft.com/sharepoint/soap/`, used by
+/// Lists.asmx, UserGroup.asmx, Copy.asmx, etc. `body` is inserted as is and must
+/// already be escaped.
+pub fn build_body_for_soap(method: &str, body: &str, namespace: Option<&str>) -> String {
+    let namespace = namespace.unwrap_or("http://schemas.microsoft.com/sharepoint/soap/");
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+  <soap:Body>
+    <{} xmlns="{}">
+      {}
+    </{}>
+  </soap:Body>
+</soap:Envelope>"#,
+        method, namespace, body, method
+    )
+}
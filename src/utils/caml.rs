@@ -0,0 +1,313 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+#[derive(Debug)]
+pub enum CamlError {
+    /// The XML itself can't be read
+    Malformed(String),
+    /// A CAML rule is broken, e.g. an `<And>` with a single child
+    Invalid(String),
+}
+
+impl std::fmt::Display for CamlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CamlError::Malformed(msg) => write!(f, "[SharepointSharp 'caml'] malformed CAML: {}", msg),
+            CamlError::Invalid(msg) => write!(f, "[SharepointSharp 'caml'] invalid CAML: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CamlError {}
+
+// Minimal tree used to rewrite CAML; attributes and text are kept raw (still escaped)
+enum Node {
+    Element {
+        name: String,
+        attributes: String,
+        children: Vec<Node>,
+    },
+    Raw(String),
+}
+
+impl Node {
+    fn to_xml(&self, xml: &mut String) {
+        match self {
+            Node::Element { name, attributes, children } if children.is_empty() => {
+                xml.push_str(&format!("<{}{}/>", name, attributes));
+            }
+            Node::Element { name, attributes, children } => {
+                xml.push_str(&format!("<{}{}>", name, attributes));
+                for child in children {
+                    child.to_xml(xml);
+                }
+                xml.push_str(&format!("</{}>", name));
+            }
+            Node::Raw(raw) => xml.push_str(raw),
+        }
+    }
+}
+
+fn is_logical(name: &str) -> bool {
+    name == "And" || name == "Or"
+}
+
+fn parse(caml: &str) -> Result<Vec<Node>, CamlError> {
+    let mut reader = Reader::from_str(caml);
+    // stack of the elements being read, the bottom one collects the top level nodes
+    let mut stack: Vec<(String, String, Vec<Node>)> = vec![(String::new(), String::new(), Vec::new())];
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| CamlError::Malformed(format!("{} at position {}", e, reader.error_position())))?;
+        match event {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                let attributes = String::from_utf8_lossy(e.attributes_raw()).to_string();
+                stack.push((name, attributes, Vec::new()));
+            }
+            Event::Empty(e) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                let attributes = String::from_utf8_lossy(e.attributes_raw()).to_string();
+                let parent = stack.last_mut().expect("the root is never popped");
+                parent.2.push(Node::Element { name, attributes, children: Vec::new() });
+            }
+            Event::End(_) => {
+                let (name, attributes, children) = stack.pop().expect("the reader checks end names");
+                let parent = stack
+                    .last_mut()
+                    .ok_or_else(|| CamlError::Malformed(format!("unexpected </{}>", name)))?;
+                parent.2.push(Node::Element { name, attributes, children });
+            }
+            Event::Text(e) => {
+                let text = String::from_utf8_lossy(&e).to_string();
+                // indentation between elements isn't meaningful
+                if !text.trim().is_empty() {
+                    stack.last_mut().expect("the root is never popped").2.push(Node::Raw(text));
+                }
+            }
+            Event::CData(e) => {
+                let text = format!("<![CDATA[{}]]>", String::from_utf8_lossy(&e));
+                stack.last_mut().expect("the root is never popped").2.push(Node::Raw(text));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if stack.len() > 1 {
+        return Err(CamlError::Malformed(format!("<{}> is never closed", stack[stack.len() - 1].0)));
+    }
+    Ok(stack.pop().map(|(_, _, nodes)| nodes).unwrap_or_default())
+}
+
+// Make every <And>/<Or> binary: <Or>a b c</Or> becomes <Or>a<Or>b c</Or></Or>
+fn normalize(node: Node) -> Result<Node, CamlError> {
+    match node {
+        Node::Element { name, attributes, children } => {
+            let mut children = children
+                .into_iter()
+                .map(normalize)
+                .collect::<Result<Vec<_>, _>>()?;
+            if is_logical(&name) {
+                if children.len() < 2 {
+                    return Err(CamlError::Invalid(format!(
+                        "<{}> needs two children, found {}",
+                        name,
+                        children.len()
+                    )));
+                }
+                children = nest(&name, children);
+            }
+            Ok(Node::Element { name, attributes, children })
+        }
+        raw => Ok(raw),
+    }
+}
+
+fn nest(name: &str, mut children: Vec<Node>) -> Vec<Node> {
+    if children.len() <= 2 {
+        return children;
+    }
+    let rest = children.split_off(1);
+    children.push(Node::Element {
+        name: name.to_string(),
+        attributes: String::new(),
+        children: nest(name, rest),
+    });
+    children
+}
+
+// Read one CAML expression (e.g. the content of a <Where>), empty input gives None
+fn parse_expression(caml: &str) -> Result<Option<Node>, CamlError> {
+    let mut nodes = parse(caml)?;
+    match nodes.len() {
+        0 => Ok(None),
+        1 => normalize(nodes.remove(0)).map(Some),
+        n => Err(CamlError::Invalid(format!(
+            "expected a single expression, found {} siblings",
+            n
+        ))),
+    }
+}
+
+/// Combine two CAML expressions (e.g. a user filter and a view filter) with `<And>`
+///
+/// Both sides must be a single expression, without the `<Where>` wrapper.
+/// `<And>`/`<Or>` with more than two children are nested so each has exactly two,
+/// as CAML requires. When one side is empty the other one is returned as is.
+pub fn combine_and(a: &str, b: &str) -> Result<String, CamlError> {
+    let mut xml = String::new();
+    match (parse_expression(a)?, parse_expression(b)?) {
+        (Some(a), Some(b)) => Node::Element {
+            name: "And".to_string(),
+            attributes: String::new(),
+            children: vec![a, b],
+        }
+        .to_xml(&mut xml),
+        (Some(node), None) | (None, Some(node)) => node.to_xml(&mut xml),
+        (None, None) => {}
+    }
+    Ok(xml)
+}
+
+fn element_children(children: &[Node]) -> impl Iterator<Item = &str> {
+    children.iter().filter_map(|child| match child {
+        Node::Element { name, .. } => Some(name.as_str()),
+        Node::Raw(_) => None,
+    })
+}
+
+fn check(node: &Node) -> Result<(), CamlError> {
+    let Node::Element { name, children, .. } = node else {
+        return Ok(());
+    };
+    let names: Vec<&str> = element_children(children).collect();
+    let count = |child: &str| names.iter().filter(|name| **name == child).count();
+
+    match name.as_str() {
+        "And" | "Or" if names.len() != 2 => {
+            return Err(CamlError::Invalid(format!(
+                "<{}> needs exactly two children, found {}",
+                name,
+                names.len()
+            )));
+        }
+        "Eq" | "Neq" | "Gt" | "Geq" | "Lt" | "Leq" | "Contains" | "BeginsWith" | "Includes" | "NotIncludes"
+            if count("FieldRef") != 1 || count("Value") != 1 =>
+        {
+            return Err(CamlError::Invalid(format!("<{}> needs one <FieldRef> and one <Value>", name)));
+        }
+        "In" if count("FieldRef") != 1 || count("Values") != 1 => {
+            return Err(CamlError::Invalid("<In> needs one <FieldRef> and one <Values>".to_string()));
+        }
+        "IsNull" | "IsNotNull" if count("FieldRef") != 1 => {
+            return Err(CamlError::Invalid(format!("<{}> needs one <FieldRef>", name)));
+        }
+        _ => {}
+    }
+
+    children.iter().try_for_each(check)
+}
+
+/// Check a CAML fragment before sending it
+///
+/// It must be well-formed XML, `<And>`/`<Or>` must have exactly two children,
+/// comparison operators (`<Eq>`, `<Geq>`, `<Contains>`, ...) one `<FieldRef>` and
+/// one `<Value>`, `<In>` one `<FieldRef>` and one `<Values>`, and
+/// `<IsNull>`/`<IsNotNull>` one `<FieldRef>`. Any fragment can be given: an
+/// expression, a `<Where>`, or a full `<Query>`.
+pub fn validate(caml: &str) -> Result<(), CamlError> {
+    parse(caml)?.iter().try_for_each(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combine_or_user_filter_with_geq_view_filter() {
+        let user = r#"<Or><Eq><FieldRef Name="Status"/><Value Type="Text">Open</Value></Eq><Eq><FieldRef Name="Status"/><Value Type="Text">Pending</Value></Eq></Or>"#;
+        let view = r#"<Geq><FieldRef Name="Created"/><Value Type="DateTime"><Today OffsetDays="-7"/></Value></Geq>"#;
+        assert_eq!(
+            combine_and(user, view).unwrap(),
+            format!("<And>{}{}</And>", user, view)
+        );
+    }
+
+    #[test]
+    fn combine_nests_nary_or() {
+        let eq = |value: &str| format!(r#"<Eq><FieldRef Name="Title"/><Value Type="Text">{}</Value></Eq>"#, value);
+        let user = format!("<Or>{}{}{}{}</Or>", eq("a"), eq("b"), eq("c"), eq("d"));
+        let view = r#"<IsNotNull><FieldRef Name="Owner"/></IsNotNull>"#;
+        let combined = combine_and(&user, view).unwrap();
+        assert_eq!(
+            combined,
+            format!(
+                "<And><Or>{}<Or>{}<Or>{}{}</Or></Or></Or>{}</And>",
+                eq("a"),
+                eq("b"),
+                eq("c"),
+                eq("d"),
+                view
+            )
+        );
+        assert!(validate(&combined).is_ok());
+    }
+
+    #[test]
+    fn combine_with_an_empty_side() {
+        let view = r#"<Eq><FieldRef Name="ID"/><Value Type="Counter">1</Value></Eq>"#;
+        assert_eq!(combine_and("", view).unwrap(), view);
+        assert_eq!(combine_and(view, "  ").unwrap(), view);
+        assert_eq!(combine_and("", "").unwrap(), "");
+    }
+
+    #[test]
+    fn combine_rejects_invalid_expressions() {
+        let eq = r#"<Eq><FieldRef Name="ID"/><Value Type="Counter">1</Value></Eq>"#;
+        assert!(matches!(combine_and("<Or>", eq), Err(CamlError::Malformed(_))));
+        assert!(matches!(combine_and(&format!("<Or>{}</Or>", eq), eq), Err(CamlError::Invalid(_))));
+        assert!(matches!(combine_and(&format!("{}{}", eq, eq), eq), Err(CamlError::Invalid(_))));
+    }
+
+    #[test]
+    fn validate_table() {
+        let eq = r#"<Eq><FieldRef Name="Title"/><Value Type="Text">a</Value></Eq>"#;
+        let cases: Vec<(String, Result<(), &str>)> = vec![
+            // well-formed vs malformed XML
+            (eq.to_string(), Ok(())),
+            (format!("<Query><Where>{}</Where></Query>", eq), Ok(())),
+            (r#"<Eq><FieldRef Name="Title"/><Value Type="Text">a</Eq>"#.to_string(), Err("malformed")),
+            (format!("<Where>{}", eq), Err("malformed")),
+            (r#"<Eq><FieldRef Name="Title/><Value>a</Value></Eq>"#.to_string(), Err("malformed")),
+            // <And>/<Or> need exactly two children
+            (format!("<And>{}{}</And>", eq, eq), Ok(())),
+            (format!("<And>{}</And>", eq), Err("invalid")),
+            (format!("<And>{}{}{}</And>", eq, eq, eq), Err("invalid")),
+            (format!("<Or>{}</Or>", eq), Err("invalid")),
+            // comparison operators need one <FieldRef> and one <Value>
+            (r#"<Eq><FieldRef Name="Title"/></Eq>"#.to_string(), Err("invalid")),
+            (r#"<Geq><Value Type="Number">1</Value></Geq>"#.to_string(), Err("invalid")),
+            (format!("<Where><And>{}<Contains><FieldRef Name=\"Body\"/></Contains></And></Where>", eq), Err("invalid")),
+            // <In> needs one <FieldRef> and one <Values>
+            (r#"<In><FieldRef Name="ID"/><Values><Value Type="Counter">1</Value><Value Type="Counter">2</Value></Values></In>"#.to_string(), Ok(())),
+            (r#"<In><FieldRef Name="ID"/><Value Type="Counter">1</Value></In>"#.to_string(), Err("invalid")),
+            (r#"<In><FieldRef Name="ID"/></In>"#.to_string(), Err("invalid")),
+            // <IsNull>/<IsNotNull> need one <FieldRef>
+            (r#"<IsNull><FieldRef Name="Owner"/></IsNull>"#.to_string(), Ok(())),
+            (r#"<IsNull></IsNull>"#.to_string(), Err("invalid")),
+            (r#"<IsNotNull><FieldRef Name="Owner"/><FieldRef Name="Editor"/></IsNotNull>"#.to_string(), Err("invalid")),
+        ];
+
+        for (caml, expected) in cases {
+            let result = match validate(&caml) {
+                Ok(()) => Ok(()),
+                Err(CamlError::Malformed(_)) => Err("malformed"),
+                Err(CamlError::Invalid(_)) => Err("invalid"),
+            };
+            assert_eq!(result, expected, "{}", caml);
+        }
+    }
+}
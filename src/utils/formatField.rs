@@ -0,0 +1,125 @@
+use crate::people::ensureUser::SpUser;
+
+/// Value of a person field, as expected by `UpdateListItems`: `ID;#DisplayName`
+pub fn format_person_field(user: &SpUser) -> String {
+    format_lookup_field(user.id, &user.title)
+}
+
+/// Value of a lookup field, as expected by `UpdateListItems`: `ID;#Value`
+pub fn format_lookup_field(id: u32, value: &str) -> String {
+    format!("{};#{}", id, value)
+}
+
+/// A field value given to `BatchBuilder`, serialized in the `;#` format SharePoint expects
+#[derive(Clone, Debug)]
+pub enum FieldValue {
+    Text(String),
+    /// One or several users: `1;#John;#2;#Jane`
+    Person(Vec<SpUser>),
+    /// One or several (ID, value) pairs: `1;#Paris;#2;#London`
+    Lookup(Vec<(u32, String)>),
+    /// `;#Red;#Blue;#`
+    MultiChoice(Vec<String>),
+}
+
+impl FieldValue {
+    pub fn to_field_string(&self) -> String {
+        match self {
+            FieldValue::Text(text) => text.clone(),
+            FieldValue::Person(users) => users.iter().map(format_person_field).collect::<Vec<_>>().join(";#"),
+            FieldValue::Lookup(values) => values
+                .iter()
+                .map(|(id, value)| format_lookup_field(*id, value))
+                .collect::<Vec<_>>()
+                .join(";#"),
+            FieldValue::MultiChoice(choices) if choices.is_empty() => String::new(),
+            FieldValue::MultiChoice(choices) => format!(";#{};#", choices.join(";#")),
+        }
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(text: String) -> Self {
+        FieldValue::Text(text)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(text: &str) -> Self {
+        FieldValue::Text(text.to_string())
+    }
+}
+
+impl From<SpUser> for FieldValue {
+    fn from(user: SpUser) -> Self {
+        FieldValue::Person(vec![user])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: u32, title: &str) -> SpUser {
+        SpUser {
+            id,
+            title: title.to_string(),
+            login_name: format!("i:0#.f|membership|{}@contoso.com", title.to_lowercase()),
+            email: format!("{}@contoso.com", title.to_lowercase()),
+        }
+    }
+
+    #[test]
+    fn person_and_lookup_fields() {
+        assert_eq!(format_person_field(&user(12, "John")), "12;#John");
+        assert_eq!(format_lookup_field(3, "Paris"), "3;#Paris");
+    }
+
+    #[test]
+    fn single_and_multi_person() {
+        assert_eq!(FieldValue::Person(vec![user(12, "John")]).to_field_string(), "12;#John");
+        assert_eq!(FieldValue::from(user(12, "John")).to_field_string(), "12;#John");
+        assert_eq!(
+            FieldValue::Person(vec![user(12, "John"), user(15, "Jane")]).to_field_string(),
+            "12;#John;#15;#Jane"
+        );
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(FieldValue::Lookup(vec![(1, "Paris".to_string())]).to_field_string(), "1;#Paris");
+        assert_eq!(
+            FieldValue::Lookup(vec![(1, "Paris".to_string()), (2, "London".to_string())]).to_field_string(),
+            "1;#Paris;#2;#London"
+        );
+    }
+
+    #[test]
+    fn multi_choice() {
+        assert_eq!(FieldValue::MultiChoice(vec![]).to_field_string(), "");
+        assert_eq!(FieldValue::MultiChoice(vec!["Red".to_string()]).to_field_string(), ";#Red;#");
+        assert_eq!(
+            FieldValue::MultiChoice(vec!["Red".to_string(), "Blue".to_string()]).to_field_string(),
+            ";#Red;#Blue;#"
+        );
+    }
+
+    #[test]
+    fn typed_values_in_a_batch() {
+        use crate::utils::batchBuilder::BatchBuilder;
+
+        let batch = BatchBuilder::new()
+            .update_item(
+                4,
+                [
+                    ("AssignedTo", FieldValue::Person(vec![user(12, "John"), user(15, "Jane")])),
+                    ("Colors", FieldValue::MultiChoice(vec!["Red".to_string()])),
+                    ("Title", "Done".into()),
+                ],
+            )
+            .build();
+        assert!(batch.contains("<Field Name=\"AssignedTo\">12;#John;#15;#Jane</Field>"));
+        assert!(batch.contains("<Field Name=\"Colors\">;#Red;#</Field>"));
+        assert!(batch.contains("<Field Name=\"Title\">Done</Field>"));
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use serde_json::Value;
+use url::Url;
+
+use super::getURL::discover_url;
+use super::resolveURL::resolve_url;
+
+// Refresh a bit before SharePoint expires the digest, so it's still valid when the request arrives
+const EXPIRATION_MARGIN: Duration = Duration::from_secs(60);
+
+/// Token to send in the `X-RequestDigest` header of REST write requests
+#[derive(Clone, Debug)]
+pub struct FormDigest {
+    pub value: String,
+    pub expires: Instant,
+}
+
+impl FormDigest {
+    pub fn is_expired(&self) -> bool {
+        Instant::now() + EXPIRATION_MARGIN >= self.expires
+    }
+}
+
+// Digests by site URL
+static SP_CACHE_FORMDIGEST: LazyLock<Mutex<HashMap<String, FormDigest>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// The cached digest of a site, unless it's about to expire
+fn cached_form_digest(key: &str) -> Option<FormDigest> {
+    SP_CACHE_FORMDIGEST
+        .lock()
+        .unwrap()
+        .get(key)
+        .filter(|digest| !digest.is_expired())
+        .cloned()
+}
+
+/// Get the form digest for a site from `_api/contextinfo`
+///
+/// The digest is cached per site URL until `FormDigestTimeoutSeconds` has
+/// (almost) elapsed, then a new one is requested.
+pub async fn get_form_digest(url: Option<&str>) -> Result<FormDigest, Box<dyn std::error::Error + Send + Sync>> {
+    let url = discover_url(url)?;
+    let key = url.to_lowercase();
+
+    if let Some(digest) = cached_form_digest(&key) {
+        return Ok(digest);
+    }
+
+    let client = Client::new();
+    let response = client
+        .post(resolve_url(&Url::parse(&url)?, "_api/contextinfo")?)
+        .header("Accept", "application/json;odata=verbose")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("[SharepointSharp 'getFormDigest'] contextinfo returned {}", response.status()).into());
+    }
+
+    let data: Value = serde_json::from_str(&response.text().await?)?;
+    let info = &data["d"]["GetContextWebInformation"];
+    let value = info["FormDigestValue"]
+        .as_str()
+        .ok_or("[SharepointSharp 'getFormDigest'] no FormDigestValue in the response")?;
+    let timeout = info["FormDigestTimeoutSeconds"].as_u64().unwrap_or(1800);
+
+    let digest = FormDigest {
+        value: value.to_string(),
+        expires: Instant::now() + Duration::from_secs(timeout),
+    };
+    SP_CACHE_FORMDIGEST.lock().unwrap().insert(key, digest.clone());
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(value: &str, expires_in: Duration) -> FormDigest {
+        FormDigest {
+            value: value.to_string(),
+            expires: Instant::now() + expires_in,
+        }
+    }
+
+    #[test]
+    fn is_expired() {
+        assert!(!digest("a", Duration::from_secs(1800)).is_expired());
+        // within the margin it's considered expired already
+        assert!(digest("a", Duration::from_secs(30)).is_expired());
+        assert!(digest("a", Duration::ZERO).is_expired());
+    }
+
+    #[test]
+    fn cached_per_site_url() {
+        let site_a = "https://contoso.sharepoint.com/sites/digest-a";
+        let site_b = "https://contoso.sharepoint.com/sites/digest-b";
+        {
+            let mut cache = SP_CACHE_FORMDIGEST.lock().unwrap();
+            cache.insert(site_a.to_string(), digest("digest-a", Duration::from_secs(1800)));
+            cache.insert(site_b.to_string(), digest("digest-b", Duration::from_secs(1800)));
+        }
+
+        // served from the cache without a request, whatever the case of the URL
+        let cached = futures::executor::block_on(get_form_digest(Some("https://Contoso.SharePoint.com/sites/Digest-A"))).unwrap();
+        assert_eq!(cached.value, "digest-a");
+        let cached = futures::executor::block_on(get_form_digest(Some(site_b))).unwrap();
+        assert_eq!(cached.value, "digest-b");
+    }
+
+    #[test]
+    fn expired_digest_is_not_served() {
+        let site = "https://contoso.sharepoint.com/sites/digest-expired";
+        SP_CACHE_FORMDIGEST
+            .lock()
+            .unwrap()
+            .insert(site.to_string(), digest("old", Duration::from_secs(10)));
+        assert!(cached_form_digest(site).is_none());
+        assert!(cached_form_digest("https://contoso.sharepoint.com/sites/unknown").is_none());
+    }
+}
@@ -0,0 +1,79 @@
+use std::env;
+
+/// Environment variable read by `discover_url` when no URL is given
+pub const SHAREPOINT_URL_VAR: &str = "SHAREPOINT_URL";
+
+#[derive(Debug)]
+pub struct UrlNotFound;
+
+impl std::fmt::Display for UrlNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[SharepointSharp 'getURL'] not able to find the URL! Provide it or set the {} environment variable.",
+            SHAREPOINT_URL_VAR
+        )
+    }
+}
+
+impl std::error::Error for UrlNotFound {}
+
+/// Find the URL of the SharePoint site to call
+///
+/// In a browser SharepointPlus reads it from the current page, but there is no
+/// `window.location` on a server, so the URL comes from, in order:
+/// 1. `base`, the URL provided by the caller (ignored when empty)
+/// 2. the `SHAREPOINT_URL` environment variable
+///
+/// The trailing `/` is removed. Build the URL of a web service from it with
+/// `resolve_url`, e.g. `resolve_url(&Url::parse(&url)?, "_vti_bin/Lists.asmx")`.
+pub fn discover_url(base: Option<&str>) -> Result<String, UrlNotFound> {
+    let from_env = env::var(SHAREPOINT_URL_VAR).ok();
+    pick_url(base, from_env.as_deref())
+}
+
+// The environment is read by the caller, so this can be tested without touching it
+fn pick_url(base: Option<&str>, from_env: Option<&str>) -> Result<String, UrlNotFound> {
+    let url = base
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .or_else(|| from_env.map(str::trim).filter(|url| !url.is_empty()))
+        .ok_or(UrlNotFound)?;
+    Ok(url.trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENV_URL: Option<&str> = Some("https://contoso.sharepoint.com/sites/env");
+
+    #[test]
+    fn explicit_base() {
+        assert_eq!(pick_url(Some("https://contoso.sharepoint.com/sites/x"), ENV_URL).unwrap(), "https://contoso.sharepoint.com/sites/x");
+        assert_eq!(pick_url(Some("  https://contoso.sharepoint.com/sites/x "), None).unwrap(), "https://contoso.sharepoint.com/sites/x");
+    }
+
+    #[test]
+    fn blank_base_falls_back_to_the_environment() {
+        assert_eq!(pick_url(Some("   "), ENV_URL).unwrap(), "https://contoso.sharepoint.com/sites/env");
+        assert_eq!(pick_url(Some(""), ENV_URL).unwrap(), "https://contoso.sharepoint.com/sites/env");
+        assert_eq!(pick_url(None, ENV_URL).unwrap(), "https://contoso.sharepoint.com/sites/env");
+    }
+
+    #[test]
+    fn trailing_slash_is_trimmed() {
+        assert_eq!(pick_url(Some("https://contoso.sharepoint.com/sites/x/"), None).unwrap(), "https://contoso.sharepoint.com/sites/x");
+        assert_eq!(pick_url(None, Some("https://contoso.sharepoint.com/sites/env//")).unwrap(), "https://contoso.sharepoint.com/sites/env");
+    }
+
+    #[test]
+    fn url_not_found() {
+        assert!(pick_url(None, None).is_err());
+        assert!(pick_url(Some(" "), Some(" ")).is_err());
+        assert_eq!(
+            UrlNotFound.to_string(),
+            "[SharepointSharp 'getURL'] not able to find the URL! Provide it or set the SHAREPOINT_URL environment variable."
+        );
+    }
+}
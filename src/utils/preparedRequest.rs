@@ -0,0 +1,21 @@
+use reqwest::{Client, Method, RequestBuilder};
+
+/// A request built by a write operation, returned instead of being sent when `dry_run` is set
+#[derive(Clone, Debug)]
+pub struct PreparedRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl PreparedRequest {
+    /// The request to send, as described
+    pub fn to_request(&self, client: &Client) -> RequestBuilder {
+        let mut request = client.request(self.method.clone(), &self.url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        request.body(self.body.clone())
+    }
+}
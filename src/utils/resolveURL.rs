@@ -0,0 +1,81 @@
+use url::{ParseError, Url};
+
+/// Resolve `path` against the URL of a site
+///
+/// - an absolute URL (`https://other/sites/y/doc.docx`) is returned as is
+/// - a server-relative path (`/sites/x/Shared Documents/doc.docx`) replaces the whole path of `base`
+/// - a site-relative path (`_vti_bin/Lists.asmx`) is appended to the path of `base`,
+///   with or without a trailing `/` on `base`
+pub fn resolve_url(base: &Url, path: &str) -> Result<Url, ParseError> {
+    match Url::parse(path) {
+        Ok(url) => return Ok(url),
+        Err(ParseError::RelativeUrlWithoutBase) => {}
+        Err(e) => return Err(e),
+    }
+
+    if path.starts_with('/') {
+        return base.join(path);
+    }
+
+    // without a trailing "/" Url::join would replace the last segment of the site path
+    let mut site = base.clone();
+    if !site.path().ends_with('/') {
+        site.set_path(&format!("{}/", site.path()));
+    }
+    site.join(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(base: &str, path: &str) -> String {
+        resolve_url(&Url::parse(base).unwrap(), path).unwrap().to_string()
+    }
+
+    #[test]
+    fn absolute_url() {
+        for base in ["https://contoso.sharepoint.com/sites/x", "https://contoso.sharepoint.com/sites/x/"] {
+            assert_eq!(
+                resolve(base, "https://other.sharepoint.com/sites/y/doc.docx"),
+                "https://other.sharepoint.com/sites/y/doc.docx"
+            );
+        }
+    }
+
+    #[test]
+    fn server_relative_path() {
+        for base in ["https://contoso.sharepoint.com/sites/x", "https://contoso.sharepoint.com/sites/x/"] {
+            assert_eq!(
+                resolve(base, "/sites/y/Shared Documents/doc.docx"),
+                "https://contoso.sharepoint.com/sites/y/Shared%20Documents/doc.docx"
+            );
+        }
+    }
+
+    #[test]
+    fn site_relative_path() {
+        for base in ["https://contoso.sharepoint.com/sites/x", "https://contoso.sharepoint.com/sites/x/"] {
+            assert_eq!(
+                resolve(base, "_vti_bin/Lists.asmx"),
+                "https://contoso.sharepoint.com/sites/x/_vti_bin/Lists.asmx"
+            );
+            assert_eq!(
+                resolve(base, "_api/web/getuserbyid(5)"),
+                "https://contoso.sharepoint.com/sites/x/_api/web/getuserbyid(5)"
+            );
+        }
+        for base in ["https://contoso.sharepoint.com", "https://contoso.sharepoint.com/"] {
+            assert_eq!(
+                resolve(base, "_api/contextinfo"),
+                "https://contoso.sharepoint.com/_api/contextinfo"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_absolute_url() {
+        let base = Url::parse("https://contoso.sharepoint.com/sites/x").unwrap();
+        assert!(resolve_url(&base, "https://exa mple.com/").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use chrono::{DateTime, NaiveDateTime, ParseError, Utc};
+
+/// Format a date the way SharePoint expects it in `<Value Type="DateTime">`
+/// when the query uses `DateInUtc`: `YYYY-MM-DDThh:mm:ssZ` (second precision)
+pub fn to_sp_date_string(date: &DateTime<Utc>) -> String {
+    date.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Parse a date returned by SharePoint, the reverse of `to_sp_date_string`
+///
+/// Accepts `YYYY-MM-DD HH:MM:SS` (as found in `ows_` attributes), ISO
+/// `YYYY-MM-DDThh:mm:ssZ`, and ISO with an offset (`+02:00`), converted to UTC.
+/// `ows_` values have no timezone and are read as UTC, but they are in the
+/// site's local time unless the query sets `<DateInUtc>TRUE</DateInUtc>` in its
+/// `<QueryOptions>`, so request it when reading dates with this function.
+pub fn parse_sp_date(date: &str) -> Result<DateTime<Utc>, ParseError> {
+    let date = date.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    let naive = NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")?;
+    Ok(naive.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone, Timelike};
+
+    #[test]
+    fn round_trip_at_second_precision() {
+        let dates = [
+            Utc.with_ymd_and_hms(2024, 2, 29, 23, 59, 59).unwrap(),
+            Utc.with_ymd_and_hms(1999, 12, 31, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2030, 7, 4, 12, 30, 5).unwrap(),
+        ];
+        for date in dates {
+            assert_eq!(parse_sp_date(&to_sp_date_string(&date)).unwrap(), date);
+        }
+
+        // sub-second precision is dropped
+        let date = Utc.with_ymd_and_hms(2024, 5, 1, 8, 15, 30).unwrap().with_nanosecond(123_456_789).unwrap();
+        assert_eq!(to_sp_date_string(&date), "2024-05-01T08:15:30Z");
+        assert_eq!(parse_sp_date(&to_sp_date_string(&date)).unwrap(), date.with_nanosecond(0).unwrap());
+    }
+
+    #[test]
+    fn ows_format() {
+        let date = parse_sp_date("2024-03-15 09:05:00").unwrap();
+        assert_eq!(date, Utc.with_ymd_and_hms(2024, 3, 15, 9, 5, 0).unwrap());
+        assert_eq!(parse_sp_date(" 2024-03-15T09:05:00Z ").unwrap(), date);
+    }
+
+    #[test]
+    fn timezones() {
+        // a local date is sent as its UTC equivalent
+        let paris = FixedOffset::east_opt(2 * 3600).unwrap();
+        let date = paris.with_ymd_and_hms(2024, 4, 1, 1, 30, 0).unwrap();
+        assert_eq!(to_sp_date_string(&date.with_timezone(&Utc)), "2024-03-31T23:30:00Z");
+
+        // an offset in the value is applied
+        let expected = Utc.with_ymd_and_hms(2024, 3, 31, 23, 30, 0).unwrap();
+        assert_eq!(parse_sp_date("2024-04-01T01:30:00+02:00").unwrap(), expected);
+        assert_eq!(parse_sp_date("2024-03-31T18:30:00-05:00").unwrap(), expected);
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert!(parse_sp_date("").is_err());
+        assert!(parse_sp_date("15/03/2024").is_err());
+        assert!(parse_sp_date("2024-02-30 10:00:00").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Attributes of an element found by `parse_elements`
+pub struct ElementAttributes<'a>(&'a BytesStart<'a>);
+
+impl ElementAttributes<'_> {
+    /// Unescaped value of an attribute, empty when it's missing
+    pub fn get(&self, name: &str) -> Result<String, quick_xml::Error> {
+        Ok(match self.0.try_get_attribute(name)? {
+            Some(attr) => attr.unescape_value()?.to_string(),
+            None => String::new(),
+        })
+    }
+}
+
+/// Map each `<local_name .../>` of a web service response from its attributes,
+/// whatever its namespace prefix
+pub fn parse_elements<T>(
+    xml: &str,
+    local_name: &str,
+    mut f: impl FnMut(&ElementAttributes) -> Result<T, quick_xml::Error>,
+) -> Result<Vec<T>, quick_xml::Error> {
+    let mut reader = Reader::from_str(xml);
+    let mut elements = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == local_name.as_bytes() => {
+                elements.push(f(&ElementAttributes(&e))?);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_of_each_element() {
+        let xml = r#"<Lists xmlns="http://schemas.microsoft.com/sharepoint/soap/">
+            <List Title="Tasks" ID="{1}"></List>
+            <ns:List Title="R&amp;D" />
+            <Other Title="nope" />
+        </Lists>"#;
+        let lists = parse_elements(xml, "List", |attrs| Ok((attrs.get("Title")?, attrs.get("ID")?))).unwrap();
+        assert_eq!(lists, [("Tasks".to_string(), "{1}".to_string()), ("R&D".to_string(), String::new())]);
+    }
+
+    #[test]
+    fn no_element() {
+        assert!(parse_elements("<Lists />", "List", |attrs| attrs.get("Title")).unwrap().is_empty());
+        assert!(parse_elements("", "List", |attrs| attrs.get("Title")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_xml() {
+        assert!(parse_elements(r#"<List Title="a &bogus; b" />"#, "List", |attrs| attrs.get("Title")).is_err());
+    }
+}